@@ -1,3 +1,6 @@
+use crate::grammar::LineGrammar;
+use crate::ids::derive_event_id;
+use crate::pricing::PricingTable;
 use crate::AgentAdapter;
 use async_trait::async_trait;
 use devlog_core::{AgentEvent, EventMetrics, EVENT_TYPE_LLM_REQUEST, EVENT_TYPE_LLM_RESPONSE, EVENT_TYPE_TOOL_USE, EVENT_TYPE_FILE_READ, EVENT_TYPE_FILE_WRITE};
@@ -14,6 +17,7 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 pub struct ClaudeAdapter {
     name: String,
     project_id: String,
+    pricing: PricingTable,
 }
 
 impl ClaudeAdapter {
@@ -21,6 +25,17 @@ impl ClaudeAdapter {
         Self {
             name: "claude".to_string(),
             project_id,
+            pricing: PricingTable::with_defaults(),
+        }
+    }
+
+    /// Override the default pricing table, e.g. to reflect custom or updated model rates
+    /// without recompiling.
+    pub fn with_pricing(project_id: String, pricing: PricingTable) -> Self {
+        Self {
+            name: "claude".to_string(),
+            project_id,
+            pricing,
         }
     }
 
@@ -36,28 +51,27 @@ impl ClaudeAdapter {
             }
         }
 
-        let msg_lower = entry.message.to_lowercase();
-        if entry.prompt.is_some() || msg_lower.contains("prompt") || msg_lower.contains("request") {
+        if entry.prompt.is_some() {
             return Some(EVENT_TYPE_LLM_REQUEST.to_string());
         }
-        if entry.response.is_some() || msg_lower.contains("response") || msg_lower.contains("completion") {
+        if entry.response.is_some() {
             return Some(EVENT_TYPE_LLM_RESPONSE.to_string());
         }
-        if entry.tool_name.is_some() || msg_lower.contains("tool") {
+        if entry.tool_name.is_some() {
             return Some(EVENT_TYPE_TOOL_USE.to_string());
         }
-        if let Some(ref _file_path) = entry.file_path {
-            if let Some(ref action) = entry.action {
-                if action == "read" || msg_lower.contains("read") {
-                    return Some(EVENT_TYPE_FILE_READ.to_string());
-                }
-                if action == "write" || msg_lower.contains("write") || msg_lower.contains("modify") {
-                    return Some(EVENT_TYPE_FILE_WRITE.to_string());
-                }
+        if let (Some(_), Some(action)) = (&entry.file_path, &entry.action) {
+            if action == "read" {
+                return Some(EVENT_TYPE_FILE_READ.to_string());
+            }
+            if action == "write" {
+                return Some(EVENT_TYPE_FILE_WRITE.to_string());
             }
         }
 
-        None
+        // Fall back to the same prioritized rule table used for plain-text lines instead of
+        // scattering ad hoc `contains` checks over the free-form message.
+        LineGrammar::classify(&entry.message).map(|t| t.to_string())
     }
 
     fn parse_timestamp(&self, ts: &Value) -> DateTime<Utc> {
@@ -146,12 +160,46 @@ impl ClaudeAdapter {
             return None;
         }
 
+        let cost = self.pricing.cost(entry.model.as_deref(), entry.prompt_tokens, entry.response_tokens);
+
         Some(EventMetrics {
             token_count: entry.tokens_used,
             duration_ms: None,
             prompt_tokens: entry.prompt_tokens,
             response_tokens: entry.response_tokens,
-            cost: None,
+            cost,
+        })
+    }
+
+    fn parse_plain_text_line(&self, line: &str, ordinal: u32) -> Option<AgentEvent> {
+        let parsed = LineGrammar::parse(line);
+        let event_type = LineGrammar::classify(&parsed.remainder)?;
+
+        let mut context = HashMap::new();
+        if let Some(level) = parsed.level {
+            context.insert("logLevel".to_string(), Value::String(level));
+        }
+        if parsed.timestamp_inferred {
+            context.insert("timestampInferred".to_string(), Value::Bool(true));
+        }
+
+        let mut data = HashMap::new();
+        data.insert("message".to_string(), Value::String(parsed.remainder));
+
+        Some(AgentEvent {
+            id: derive_event_id(line, event_type, ordinal),
+            timestamp: parsed.timestamp,
+            event_type: event_type.to_string(),
+            agent_id: self.name.clone(),
+            agent_version: "".to_string(),
+            session_id: Uuid::new_v4().to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: Some(self.project_id.clone()),
+            context,
+            data,
+            metrics: None,
         })
     }
 }
@@ -163,6 +211,10 @@ impl AgentAdapter for ClaudeAdapter {
     }
 
     fn parse_log_line(&self, line: &str) -> Result<Option<AgentEvent>> {
+        self.parse_log_line_at(line, 0)
+    }
+
+    fn parse_log_line_at(&self, line: &str, ordinal: u32) -> Result<Option<AgentEvent>> {
         let line = line.trim();
         if line.is_empty() {
             return Ok(None);
@@ -170,7 +222,7 @@ impl AgentAdapter for ClaudeAdapter {
 
         let entry: ClaudeLogEntry = match serde_json::from_str(line) {
             Ok(e) => e,
-            Err(_) => return Ok(None),
+            Err(_) => return Ok(self.parse_plain_text_line(line, ordinal)),
         };
 
         let event_type = match self.detect_event_type(&entry) {
@@ -180,7 +232,7 @@ impl AgentAdapter for ClaudeAdapter {
 
         let timestamp = self.parse_timestamp(&entry.timestamp);
         let event = AgentEvent {
-            id: Uuid::new_v4().to_string(),
+            id: derive_event_id(line, &event_type, ordinal),
             timestamp,
             event_type: event_type.clone(),
             agent_id: self.name.clone(),
@@ -202,27 +254,57 @@ impl AgentAdapter for ClaudeAdapter {
         let file = File::open(file_path).await.context("failed to open log file")?;
         let reader = BufReader::new(file);
         let mut lines = reader.lines();
-        let mut events = Vec::new();
+        let mut events: Vec<AgentEvent> = Vec::new();
+        let mut ordinal: u32 = 0;
 
         while let Some(line) = lines.next_line().await.context("failed to read line")? {
-            if let Some(event) = self.parse_log_line(&line)? {
+            // Continuation/indented lines with no timestamp of their own belong to the
+            // previous event rather than standing alone.
+            if LineGrammar::is_continuation(&line) {
+                if let Some(last) = events.last_mut() {
+                    if let Some(Value::String(message)) = last.data.get_mut("message") {
+                        message.push('\n');
+                        message.push_str(line.trim());
+                    }
+                    continue;
+                }
+            }
+
+            if let Some(event) = self.parse_log_line_at(&line, ordinal)? {
                 events.push(event);
             }
+            ordinal += 1;
         }
 
         Ok(events)
     }
 
-    fn supports_format(&self, sample: &str) -> bool {
+    async fn parse_log_file_parallel(&self, file_path: &Path, workers: Option<usize>) -> Result<Vec<AgentEvent>> {
+        let bytes = tokio::fs::read(file_path).await.context("failed to read log file")?;
+        let workers = workers.unwrap_or_else(crate::parallel::default_worker_count);
+        crate::parallel::parse_chunks_parallel(&bytes, workers, |line, ordinal| self.parse_log_line_at(line, ordinal))
+    }
+
+    fn detection_confidence(&self, sample: &str) -> f64 {
         let entry: ClaudeLogEntry = match serde_json::from_str(sample) {
             Ok(e) => e,
-            Err(_) => return false,
+            Err(_) => return 0.0,
         };
 
-        entry.conversation_id.is_some() || 
-        entry.model.is_some() || 
-        entry.message.to_lowercase().contains("claude") || 
-        entry.message.to_lowercase().contains("anthropic")
+        let message_lower = entry.message.to_lowercase();
+        let vendor_keyword = message_lower.contains("claude") || message_lower.contains("anthropic");
+        let has_conversation_id = entry.conversation_id.is_some();
+
+        if (has_conversation_id || entry.model.is_some()) && vendor_keyword {
+            0.95
+        } else if has_conversation_id || entry.model.is_some() {
+            0.6
+        } else if vendor_keyword {
+            0.5
+        } else {
+            // Generic JSON shape with no vendor-identifying fields.
+            0.1
+        }
     }
 }
 
@@ -1,4 +1,6 @@
-use crate::AgentAdapter;
+use crate::{AgentAdapter, Checkpoint};
+use crate::ids::derive_event_id;
+use crate::tokenizer::TokenizerRegistry;
 use async_trait::async_trait;
 use devlog_core::{AgentEvent, EventMetrics, EVENT_TYPE_LLM_REQUEST, EVENT_TYPE_LLM_RESPONSE, EVENT_TYPE_TOOL_USE, EVENT_TYPE_FILE_READ, EVENT_TYPE_FILE_MODIFY};
 use std::path::Path;
@@ -6,13 +8,13 @@ use anyhow::{Result, Context, anyhow};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use chrono::{DateTime, Utc, TimeZone};
-use std::collections::HashMap;
-use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
 use tokio::fs;
 
 pub struct CopilotAdapter {
     name: String,
     project_id: String,
+    tokenizers: TokenizerRegistry,
 }
 
 impl CopilotAdapter {
@@ -20,6 +22,17 @@ impl CopilotAdapter {
         Self {
             name: "github-copilot".to_string(),
             project_id,
+            tokenizers: TokenizerRegistry::with_defaults(),
+        }
+    }
+
+    /// Override the default tokenizer registry, e.g. to register real per-model BPE
+    /// vocabularies instead of the built-in approximate merge table.
+    pub fn with_tokenizers(project_id: String, tokenizers: TokenizerRegistry) -> Self {
+        Self {
+            name: "github-copilot".to_string(),
+            project_id,
+            tokenizers,
         }
     }
 
@@ -86,8 +99,163 @@ impl CopilotAdapter {
         "".to_string()
     }
 
-    fn estimate_tokens(&self, text: &str) -> i32 {
-        (text.split_whitespace().count() as f64 * 1.3) as i32
+    fn estimate_tokens(&self, model_id: &str, text: &str) -> i32 {
+        self.tokenizers.for_model(model_id).count_tokens(text)
+    }
+
+    /// Build every event for one (non-canceled) request: the LLM request/response pair, file
+    /// references, and tool/edit invocations. Shared by `parse_log_file` and
+    /// `parse_log_file_since`, which differ only in which requests they call this for.
+    fn events_for_request(
+        &self,
+        request: &CopilotRequest,
+        session_id: &str,
+        workspace_id: &str,
+        requester_username: &str,
+    ) -> Vec<AgentEvent> {
+        let mut events = Vec::new();
+        let mut ordinal: u32 = 0;
+        let timestamp = self.parse_timestamp(&request.timestamp);
+
+        // 1. LLM Request Event
+        let prompt_text = request.message.text.clone();
+        events.push(AgentEvent {
+            id: derive_event_id(&request.request_id, EVENT_TYPE_LLM_REQUEST, ordinal),
+            timestamp,
+            event_type: EVENT_TYPE_LLM_REQUEST.to_string(),
+            agent_id: self.name.clone(),
+            agent_version: "1.0.0".to_string(),
+            session_id: session_id.to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: Some(self.project_id.clone()),
+            context: HashMap::from([
+                ("username".to_string(), Value::String(requester_username.to_string())),
+                ("workspaceId".to_string(), Value::String(workspace_id.to_string())),
+            ]),
+            data: HashMap::from([
+                ("requestId".to_string(), Value::String(request.request_id.clone())),
+                ("modelId".to_string(), Value::String(request.model_id.clone())),
+                ("prompt".to_string(), Value::String(prompt_text.clone())),
+                ("promptLength".to_string(), Value::Number(prompt_text.len().into())),
+            ]),
+            metrics: Some(EventMetrics {
+                prompt_tokens: Some(self.estimate_tokens(&request.model_id, &prompt_text)),
+                ..Default::default()
+            }),
+        });
+        ordinal += 1;
+
+        // 2. File References from variables
+        for var in &request.variable_data.variables {
+            let file_path = self.extract_file_path(&Value::Object(var.value.clone().into_iter().collect()));
+            if !file_path.is_empty() {
+                events.push(AgentEvent {
+                    id: derive_event_id(&request.request_id, EVENT_TYPE_FILE_READ, ordinal),
+                    timestamp,
+                    event_type: EVENT_TYPE_FILE_READ.to_string(),
+                    agent_id: self.name.clone(),
+                    agent_version: "1.0.0".to_string(),
+                    session_id: session_id.to_string(),
+                    project_id: 0,
+                    machine_id: None,
+                    workspace_id: None,
+                    legacy_project_id: Some(self.project_id.clone()),
+                    context: HashMap::new(),
+                    data: HashMap::from([
+                        ("requestId".to_string(), Value::String(request.request_id.clone())),
+                        ("filePath".to_string(), Value::String(file_path)),
+                        ("variableName".to_string(), Value::String(var.name.clone())),
+                    ]),
+                    metrics: None,
+                });
+                ordinal += 1;
+            }
+        }
+
+        // 3. Tool Invocations and Response Text
+        let mut response_text_parts = Vec::new();
+        for item in &request.response {
+            match item.kind.as_deref() {
+                None => {
+                    let text = self.extract_value_as_string(&item.value);
+                    if !text.is_empty() {
+                        response_text_parts.push(text);
+                    }
+                }
+                Some("toolInvocationSerialized") => {
+                    events.push(AgentEvent {
+                        id: derive_event_id(&request.request_id, EVENT_TYPE_TOOL_USE, ordinal),
+                        timestamp: timestamp + chrono::Duration::milliseconds(100),
+                        event_type: EVENT_TYPE_TOOL_USE.to_string(),
+                        agent_id: self.name.clone(),
+                        agent_version: "1.0.0".to_string(),
+                        session_id: session_id.to_string(),
+                        project_id: 0,
+                        machine_id: None,
+                        workspace_id: None,
+                        legacy_project_id: Some(self.project_id.clone()),
+                        context: HashMap::new(),
+                        data: HashMap::from([
+                            ("requestId".to_string(), Value::String(request.request_id.clone())),
+                            ("toolId".to_string(), Value::String(item.tool_id.clone().unwrap_or_default())),
+                            ("toolName".to_string(), Value::String(item.tool_name.clone().unwrap_or_default())),
+                        ]),
+                        metrics: None,
+                    });
+                    ordinal += 1;
+                }
+                Some("textEditGroup") => {
+                    events.push(AgentEvent {
+                        id: derive_event_id(&request.request_id, EVENT_TYPE_FILE_MODIFY, ordinal),
+                        timestamp: timestamp + chrono::Duration::milliseconds(200),
+                        event_type: EVENT_TYPE_FILE_MODIFY.to_string(),
+                        agent_id: self.name.clone(),
+                        agent_version: "1.0.0".to_string(),
+                        session_id: session_id.to_string(),
+                        project_id: 0,
+                        machine_id: None,
+                        workspace_id: None,
+                        legacy_project_id: Some(self.project_id.clone()),
+                        context: HashMap::new(),
+                        data: HashMap::from([
+                            ("requestId".to_string(), Value::String(request.request_id.clone())),
+                        ]),
+                        metrics: None,
+                    });
+                    ordinal += 1;
+                }
+                _ => {}
+            }
+        }
+
+        // 4. LLM Response Event
+        let response_text = response_text_parts.join("");
+        events.push(AgentEvent {
+            id: derive_event_id(&request.request_id, EVENT_TYPE_LLM_RESPONSE, ordinal),
+            timestamp: timestamp + chrono::Duration::seconds(1),
+            event_type: EVENT_TYPE_LLM_RESPONSE.to_string(),
+            agent_id: self.name.clone(),
+            agent_version: "1.0.0".to_string(),
+            session_id: session_id.to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: Some(self.project_id.clone()),
+            context: HashMap::new(),
+            data: HashMap::from([
+                ("requestId".to_string(), Value::String(request.request_id.clone())),
+                ("response".to_string(), Value::String(response_text.clone())),
+                ("responseLength".to_string(), Value::Number(response_text.len().into())),
+            ]),
+            metrics: Some(EventMetrics {
+                response_tokens: Some(self.estimate_tokens(&request.model_id, &response_text)),
+                ..Default::default()
+            }),
+        });
+
+        events
     }
 }
 
@@ -109,158 +277,50 @@ impl AgentAdapter for CopilotAdapter {
         let workspace_id = self.extract_workspace_id(file_path);
 
         let mut events = Vec::new();
-
-        for request in session.requests {
+        for request in &session.requests {
             if request.is_canceled {
                 continue;
             }
+            events.extend(self.events_for_request(request, &session_id, &workspace_id, &session.requester_username));
+        }
 
-            let timestamp = self.parse_timestamp(&request.timestamp);
-            
-            // 1. LLM Request Event
-            let prompt_text = request.message.text.clone();
-            let mut req_event = AgentEvent {
-                id: Uuid::new_v4().to_string(),
-                timestamp,
-                event_type: EVENT_TYPE_LLM_REQUEST.to_string(),
-                agent_id: self.name.clone(),
-                agent_version: "1.0.0".to_string(),
-                session_id: session_id.clone(),
-                project_id: 0,
-                machine_id: None,
-                workspace_id: None,
-                legacy_project_id: Some(self.project_id.clone()),
-                context: HashMap::from([
-                    ("username".to_string(), Value::String(session.requester_username.clone())),
-                    ("workspaceId".to_string(), Value::String(workspace_id.clone())),
-                ]),
-                data: HashMap::from([
-                    ("requestId".to_string(), Value::String(request.request_id.clone())),
-                    ("modelId".to_string(), Value::String(request.model_id.clone())),
-                    ("prompt".to_string(), Value::String(prompt_text.clone())),
-                    ("promptLength".to_string(), Value::Number(prompt_text.len().into())),
-                ]),
-                metrics: Some(EventMetrics {
-                    prompt_tokens: Some(self.estimate_tokens(&prompt_text)),
-                    ..Default::default()
-                }),
-            };
-            events.push(req_event);
-
-            // 2. File References from variables
-            for var in request.variable_data.variables {
-                let file_path = self.extract_file_path(&Value::Object(var.value.clone().into_iter().collect()));
-                if !file_path.is_empty() {
-                    events.push(AgentEvent {
-                        id: Uuid::new_v4().to_string(),
-                        timestamp,
-                        event_type: EVENT_TYPE_FILE_READ.to_string(),
-                        agent_id: self.name.clone(),
-                        agent_version: "1.0.0".to_string(),
-                        session_id: session_id.clone(),
-                        project_id: 0,
-                        machine_id: None,
-                        workspace_id: None,
-                        legacy_project_id: Some(self.project_id.clone()),
-                        context: HashMap::new(),
-                        data: HashMap::from([
-                            ("requestId".to_string(), Value::String(request.request_id.clone())),
-                            ("filePath".to_string(), Value::String(file_path)),
-                            ("variableName".to_string(), Value::String(var.name)),
-                        ]),
-                        metrics: None,
-                    });
-                }
-            }
+        Ok(events)
+    }
 
-            // 3. Tool Invocations and Response Text
-            let mut response_text_parts = Vec::new();
-            for item in request.response {
-                match item.kind.as_deref() {
-                    None => {
-                        let text = self.extract_value_as_string(&item.value);
-                        if !text.is_empty() {
-                            response_text_parts.push(text);
-                        }
-                    }
-                    Some("toolInvocationSerialized") => {
-                        events.push(AgentEvent {
-                            id: Uuid::new_v4().to_string(),
-                            timestamp: timestamp + chrono::Duration::milliseconds(100),
-                            event_type: EVENT_TYPE_TOOL_USE.to_string(),
-                            agent_id: self.name.clone(),
-                            agent_version: "1.0.0".to_string(),
-                            session_id: session_id.clone(),
-                            project_id: 0,
-                            machine_id: None,
-                            workspace_id: None,
-                            legacy_project_id: Some(self.project_id.clone()),
-                            context: HashMap::new(),
-                            data: HashMap::from([
-                                ("requestId".to_string(), Value::String(request.request_id.clone())),
-                                ("toolId".to_string(), Value::String(item.tool_id.unwrap_or_default())),
-                                ("toolName".to_string(), Value::String(item.tool_name.unwrap_or_default())),
-                            ]),
-                            metrics: None,
-                        });
-                    }
-                    Some("textEditGroup") => {
-                        events.push(AgentEvent {
-                            id: Uuid::new_v4().to_string(),
-                            timestamp: timestamp + chrono::Duration::milliseconds(200),
-                            event_type: EVENT_TYPE_FILE_MODIFY.to_string(),
-                            agent_id: self.name.clone(),
-                            agent_version: "1.0.0".to_string(),
-                            session_id: session_id.clone(),
-                            project_id: 0,
-                            machine_id: None,
-                            workspace_id: None,
-                            legacy_project_id: Some(self.project_id.clone()),
-                            context: HashMap::new(),
-                            data: HashMap::from([
-                                ("requestId".to_string(), Value::String(request.request_id.clone())),
-                            ]),
-                            metrics: None,
-                        });
-                    }
-                    _ => {}
-                }
-            }
+    /// Skip requests whose id is already in `checkpoint`'s `SeenIds` set, since a Copilot chat
+    /// session grows by appending whole requests rather than lines. The returned checkpoint is
+    /// the union of what was already seen and every non-canceled request's id in this parse
+    /// (including ones skipped this time), so it stays valid across repeated calls.
+    async fn parse_log_file_since(&self, file_path: &Path, checkpoint: Option<Checkpoint>) -> Result<(Vec<AgentEvent>, Checkpoint)> {
+        let data = fs::read_to_string(file_path).await.context("failed to read chat session file")?;
+        let session: CopilotChatSession = serde_json::from_str(&data).context("failed to parse chat session JSON")?;
 
-            // 4. LLM Response Event
-            let response_text = response_text_parts.join("");
-            events.push(AgentEvent {
-                id: Uuid::new_v4().to_string(),
-                timestamp: timestamp + chrono::Duration::seconds(1),
-                event_type: EVENT_TYPE_LLM_RESPONSE.to_string(),
-                agent_id: self.name.clone(),
-                agent_version: "1.0.0".to_string(),
-                session_id: session_id.clone(),
-                project_id: 0,
-                machine_id: None,
-                workspace_id: None,
-                legacy_project_id: Some(self.project_id.clone()),
-                context: HashMap::new(),
-                data: HashMap::from([
-                    ("requestId".to_string(), Value::String(request.request_id.clone())),
-                    ("response".to_string(), Value::String(response_text.clone())),
-                    ("responseLength".to_string(), Value::Number(response_text.len().into())),
-                ]),
-                metrics: Some(EventMetrics {
-                    response_tokens: Some(self.estimate_tokens(&response_text)),
-                    ..Default::default()
-                }),
-            });
+        let mut seen = match checkpoint {
+            Some(Checkpoint::SeenIds(ids)) => ids,
+            _ => HashSet::new(),
+        };
+
+        let session_id = self.extract_session_id(file_path);
+        let workspace_id = self.extract_workspace_id(file_path);
+
+        let mut events = Vec::new();
+        for request in &session.requests {
+            if request.is_canceled {
+                continue;
+            }
+            if seen.insert(request.request_id.clone()) {
+                events.extend(self.events_for_request(request, &session_id, &workspace_id, &session.requester_username));
+            }
         }
 
-        Ok(events)
+        Ok((events, Checkpoint::SeenIds(seen)))
     }
 
-    fn supports_format(&self, sample: &str) -> bool {
-        let session: Result<CopilotChatSession, _> = serde_json::from_str(sample);
-        match session {
-            Ok(s) => s.version > 0 && !s.requests.is_empty(),
-            Err(_) => false,
+    fn detection_confidence(&self, sample: &str) -> f64 {
+        match serde_json::from_str::<CopilotChatSession>(sample) {
+            Ok(s) if s.version > 0 && !s.requests.is_empty() => 0.9,
+            Ok(_) => 0.2,
+            Err(_) => 0.0,
         }
     }
 }
@@ -0,0 +1,182 @@
+//! Shared plain-text log grammar used by adapters that fall back to unstructured lines
+//! (currently `CursorAdapter` and `ClaudeAdapter`) when a line isn't valid JSON.
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use devlog_core::{
+    EVENT_TYPE_FILE_READ, EVENT_TYPE_FILE_WRITE, EVENT_TYPE_LLM_REQUEST, EVENT_TYPE_LLM_RESPONSE,
+    EVENT_TYPE_TOOL_USE,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Leading-timestamp patterns tried in order. Each captures the timestamp text in group 1;
+/// the grammar strips the full match (group 0) before continuing with level/classification.
+static TIMESTAMP_PATTERNS: Lazy<Vec<(Regex, &'static str)>> = Lazy::new(|| {
+    vec![
+        (
+            Regex::new(r"^\[(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?)\]\s*").unwrap(),
+            "bracketed",
+        ),
+        (
+            Regex::new(r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2}))\s*")
+                .unwrap(),
+            "rfc3339",
+        ),
+        (Regex::new(r"^\[(\d{10,13})\]\s*").unwrap(), "epoch"),
+        (
+            Regex::new(r"^([A-Z][a-z]{2}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2})\s*").unwrap(),
+            "syslog",
+        ),
+    ]
+});
+
+static LEVEL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(INFO|DEBUG|WARN|ERROR|TRACE)\b\s*").unwrap());
+
+/// Classification rules for the post-timestamp/level remainder, evaluated in order;
+/// the first matching rule wins. Keeping this as an explicit table (rather than scattered
+/// `contains` checks) makes precedence testable.
+static CLASSIFICATION_RULES: Lazy<Vec<(Regex, &'static str)>> = Lazy::new(|| {
+    vec![
+        (Regex::new(r"(?i)\btool\b").unwrap(), EVENT_TYPE_TOOL_USE),
+        (
+            Regex::new(r"(?i)\b(response|completion)\b").unwrap(),
+            EVENT_TYPE_LLM_RESPONSE,
+        ),
+        (
+            Regex::new(r"(?i)\b(prompt|request)\b").unwrap(),
+            EVENT_TYPE_LLM_REQUEST,
+        ),
+        (Regex::new(r"(?i)\bread\b").unwrap(), EVENT_TYPE_FILE_READ),
+        (
+            Regex::new(r"(?i)\b(write|modify)\b").unwrap(),
+            EVENT_TYPE_FILE_WRITE,
+        ),
+    ]
+});
+
+/// Result of deconstructing a semi-structured text line into its leading timestamp, optional
+/// level token, and the remaining text to classify.
+pub struct ParsedLine {
+    pub timestamp: DateTime<Utc>,
+    pub timestamp_inferred: bool,
+    pub level: Option<String>,
+    pub remainder: String,
+}
+
+pub struct LineGrammar;
+
+impl LineGrammar {
+    /// Strip a leading timestamp (if any) and level token (if any) from `line`, returning the
+    /// parsed fields and the remaining text. A line with no recognizable timestamp still parses,
+    /// falling back to `Utc::now()` with `timestamp_inferred` set.
+    pub fn parse(line: &str) -> ParsedLine {
+        let mut rest = line;
+        let mut timestamp = None;
+
+        for (pattern, kind) in TIMESTAMP_PATTERNS.iter() {
+            if let Some(m) = pattern.captures(rest) {
+                let captured = m.get(1).unwrap().as_str();
+                timestamp = parse_timestamp(captured, kind);
+                rest = &rest[m.get(0).unwrap().end()..];
+                break;
+            }
+        }
+
+        let (timestamp, timestamp_inferred) = match timestamp {
+            Some(ts) => (ts, false),
+            None => (Utc::now(), true),
+        };
+
+        let mut level = None;
+        if let Some(m) = LEVEL_PATTERN.captures(rest) {
+            level = Some(m.get(1).unwrap().as_str().to_string());
+            rest = &rest[m.get(0).unwrap().end()..];
+        }
+
+        ParsedLine {
+            timestamp,
+            timestamp_inferred,
+            level,
+            remainder: rest.trim().to_string(),
+        }
+    }
+
+    /// Classify a line's remainder into an event type using the prioritized rule table.
+    pub fn classify(remainder: &str) -> Option<&'static str> {
+        CLASSIFICATION_RULES
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(remainder))
+            .map(|(_, event_type)| *event_type)
+    }
+
+    /// A continuation/indented line carries no timestamp of its own and should be appended to
+    /// the previous event's message rather than emitted as a new event.
+    pub fn is_continuation(line: &str) -> bool {
+        if line.trim().is_empty() {
+            return false;
+        }
+        if !(line.starts_with(' ') || line.starts_with('\t')) {
+            return false;
+        }
+        let trimmed = line.trim_start();
+        !TIMESTAMP_PATTERNS.iter().any(|(p, _)| p.is_match(trimmed))
+    }
+}
+
+fn parse_timestamp(text: &str, kind: &str) -> Option<DateTime<Utc>> {
+    match kind {
+        "bracketed" => NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+            .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S"))
+            .ok()
+            .map(|dt| Utc.from_utc_datetime(&dt)),
+        "rfc3339" => DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc)),
+        "epoch" => text.parse::<i64>().ok().and_then(|secs| {
+            let secs = if text.len() > 10 { secs / 1000 } else { secs };
+            Utc.timestamp_opt(secs, 0).single()
+        }),
+        "syslog" => {
+            let year = Utc::now().format("%Y").to_string();
+            let with_year = format!("{} {}", year, text);
+            NaiveDateTime::parse_from_str(&with_year, "%Y %b %d %H:%M:%S")
+                .ok()
+                .map(|dt| Utc.from_utc_datetime(&dt))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bracketed_timestamp_and_level() {
+        let parsed = LineGrammar::parse("[2025-10-31 10:00:00] INFO Cursor AI completion requested");
+        assert!(!parsed.timestamp_inferred);
+        assert_eq!(parsed.level, Some("INFO".to_string()));
+        assert_eq!(parsed.remainder, "Cursor AI completion requested");
+    }
+
+    #[test]
+    fn test_parse_missing_timestamp_is_inferred() {
+        let parsed = LineGrammar::parse("a tool was invoked");
+        assert!(parsed.timestamp_inferred);
+        assert_eq!(parsed.remainder, "a tool was invoked");
+    }
+
+    #[test]
+    fn test_classify_prioritizes_tool_over_request() {
+        assert_eq!(LineGrammar::classify("tool request dispatched"), Some(EVENT_TYPE_TOOL_USE));
+        assert_eq!(LineGrammar::classify("prompt sent"), Some(EVENT_TYPE_LLM_REQUEST));
+        assert_eq!(LineGrammar::classify("nothing interesting"), None);
+    }
+
+    #[test]
+    fn test_is_continuation() {
+        assert!(LineGrammar::is_continuation("    stack trace line 2"));
+        assert!(!LineGrammar::is_continuation("[2025-10-31 10:00:00] INFO tool call"));
+        assert!(!LineGrammar::is_continuation("not indented"));
+    }
+}
@@ -0,0 +1,16 @@
+//! Shared helper for deriving stable `AgentEvent.id`s from an adapter's own identifying fields,
+//! used by adapters whose source format has no native event id to reuse directly.
+use std::hash::{Hash, Hasher};
+
+/// Derive a stable `AgentEvent.id` from the fields that identify an event (e.g. a Copilot
+/// `requestId`, or the raw line text for a line-oriented log), so re-parsing the same source
+/// (after a crash between emit and checkpoint-write, or a `backfill` rerun) reproduces the same
+/// id instead of a fresh one downstream dedup (e.g. `BackfillManager::event_digest`) can't
+/// recognize.
+pub fn derive_event_id(key: &str, event_type: &str, ordinal: u32) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    event_type.hash(&mut hasher);
+    ordinal.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
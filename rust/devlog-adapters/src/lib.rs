@@ -1,18 +1,150 @@
 use async_trait::async_trait;
 use devlog_core::AgentEvent;
 use std::path::Path;
-use anyhow::Result;
+use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// Opaque per-adapter resume token for [`AgentAdapter::parse_log_file_since`], persisted by
+/// callers (e.g. the file watcher) between calls so re-parsing a source only emits what's new.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Checkpoint {
+    /// For line-oriented logs: how many bytes were already consumed, plus a hash of the file's
+    /// leading bytes so a rotation/truncation (identity changed) is detected and parsing
+    /// restarts from 0 rather than seeking into unrelated content. `next_ordinal` carries the
+    /// file-wide line counter forward across calls so two textually-identical lines in separate
+    /// polls (e.g. repeated heartbeat lines) still derive distinct ids instead of colliding the
+    /// way they would if ordinals restarted from 0 every call. `#[serde(default)]` so checkpoints
+    /// persisted before this field existed still deserialize, just restarting the counter at 0.
+    ByteOffset {
+        offset: u64,
+        fingerprint: u64,
+        #[serde(default)]
+        next_ordinal: u32,
+    },
+    /// For sources that grow by appending whole records to an existing list (e.g. Copilot chat
+    /// sessions appending requests) rather than appending lines: the ids already emitted.
+    SeenIds(HashSet<String>),
+}
+
+/// How many leading bytes of a file to hash for the default `ByteOffset` checkpoint's rotation
+/// check.
+const CHECKPOINT_FINGERPRINT_SAMPLE_BYTES: usize = 4096;
+
+fn fingerprint_sample(bytes: &[u8]) -> u64 {
+    let sample_len = bytes.len().min(CHECKPOINT_FINGERPRINT_SAMPLE_BYTES);
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes[..sample_len].hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Parse as many complete (newline-terminated) lines as are available in `bytes` past `start`,
+/// leaving a dangling partial final line unconsumed rather than mis-parsing it as a whole line —
+/// the file may still be mid-write, and the next call will see the completed line once it is.
+/// Mirrors `stream.rs::parse_log_stream`'s buffering for incremental reads. Returns the parsed
+/// events, the byte offset through the last complete line, and the next ordinal to resume from —
+/// `start_ordinal` continues a file-wide counter rather than restarting at 0 each call, so a line
+/// repeated across separate polls still gets a distinct ordinal (and so a distinct id) each time.
+fn parse_complete_lines_since(
+    bytes: &[u8],
+    start: usize,
+    start_ordinal: u32,
+    mut parse_line: impl FnMut(&str, u32) -> Result<Option<AgentEvent>>,
+) -> Result<(Vec<AgentEvent>, usize, u32)> {
+    let tail = &bytes[start..];
+    let Some(consumed) = tail.iter().rposition(|&b| b == b'\n').map(|pos| pos + 1) else {
+        return Ok((Vec::new(), start, start_ordinal));
+    };
+
+    let text = String::from_utf8_lossy(&tail[..consumed]);
+    let mut events = Vec::new();
+    let mut ordinal = start_ordinal;
+    for line in text.lines() {
+        if let Some(event) = parse_line(line, ordinal)? {
+            events.push(event);
+        }
+        ordinal = ordinal.wrapping_add(1);
+    }
+
+    Ok((events, start + consumed, ordinal))
+}
 
 #[async_trait]
 pub trait AgentAdapter: Send + Sync {
     fn name(&self) -> &str;
     fn parse_log_line(&self, line: &str) -> Result<Option<AgentEvent>>;
     async fn parse_log_file(&self, file_path: &Path) -> Result<Vec<AgentEvent>>;
-    fn supports_format(&self, sample: &str) -> bool;
+
+    /// Like `parse_log_line`, but for adapters that derive an event's id from the raw line text
+    /// (via `ids::derive_event_id`) and so need `ordinal` — the line's position within the
+    /// current parse pass — to keep two textually-identical lines from deriving the same id.
+    /// Defaults to ignoring `ordinal` and delegating to `parse_log_line`, which is correct for
+    /// adapters whose ids already come from a native, inherently-unique field (e.g.
+    /// `CopilotAdapter`'s `requestId`) or that don't parse line-by-line at all.
+    fn parse_log_line_at(&self, line: &str, ordinal: u32) -> Result<Option<AgentEvent>> {
+        let _ = ordinal;
+        self.parse_log_line(line)
+    }
+
+    /// Confidence in `0.0..=1.0` that `sample` belongs to this adapter's format, used to rank
+    /// candidates during auto-detection. Adapters that can distinguish strong vs. weak matches
+    /// should return graded scores rather than just 0.0/1.0.
+    fn detection_confidence(&self, sample: &str) -> f64;
+
+    /// Whether `sample` belongs to this adapter's format at all, for callers that only need a
+    /// yes/no answer. Defaults to mapping the new confidence score onto the old boolean: any
+    /// nonzero confidence counts as a match.
+    fn supports_format(&self, sample: &str) -> bool {
+        self.detection_confidence(sample) > 0.0
+    }
+
+    /// Parse `file_path` using a worker pool sized to available CPUs (or `workers` if given),
+    /// for adapters whose `parse_log_line` is pure and safe to fan out. Defaults to the
+    /// sequential `parse_log_file`; stateful adapters (e.g. `CopilotAdapter`, which parses one
+    /// whole-file JSON document rather than independent lines) should leave this default.
+    async fn parse_log_file_parallel(&self, file_path: &Path, _workers: Option<usize>) -> Result<Vec<AgentEvent>> {
+        self.parse_log_file(file_path).await
+    }
+
+    /// Parse only what's new since `checkpoint` (`None` means "from the start"), returning the
+    /// new events plus an updated checkpoint to persist for next time. Defaults to treating the
+    /// source as a line-oriented log: re-parses the bytes after the checkpoint's offset unless
+    /// the file's leading-byte fingerprint no longer matches (rotation/truncation), in which case
+    /// it restarts from 0. A dangling partial final line (the file may still be mid-write) is left
+    /// unconsumed and the checkpoint only advances past complete lines, so it's picked up whole on
+    /// the next call instead of being mis-parsed as a fragment. Adapters with list-based sources
+    /// (e.g. `CopilotAdapter`) override this.
+    async fn parse_log_file_since(&self, file_path: &Path, checkpoint: Option<Checkpoint>) -> Result<(Vec<AgentEvent>, Checkpoint)> {
+        let bytes = tokio::fs::read(file_path).await.context("failed to read log file")?;
+
+        let (start, start_ordinal) = match &checkpoint {
+            Some(Checkpoint::ByteOffset { offset, fingerprint: previous, next_ordinal })
+                if (*offset as usize) <= bytes.len() && fingerprint_sample(&bytes[..*offset as usize]) == *previous =>
+            {
+                (*offset as usize, *next_ordinal)
+            }
+            _ => (0, 0),
+        };
+
+        let (events, consumed, next_ordinal) =
+            parse_complete_lines_since(&bytes, start, start_ordinal, |line, ordinal| self.parse_log_line_at(line, ordinal))?;
+        let fingerprint = fingerprint_sample(&bytes[..consumed]);
+
+        Ok((events, Checkpoint::ByteOffset { offset: consumed as u64, fingerprint, next_ordinal }))
+    }
 }
 
 pub mod claude;
 pub mod copilot;
+pub mod cursor;
+pub mod grammar;
+pub mod ids;
+pub mod parallel;
+pub mod pricing;
 pub mod registry;
+pub mod stream;
+pub mod tokenizer;
 
 pub use registry::Registry;
+pub use stream::parse_log_stream;
@@ -0,0 +1,249 @@
+//! Shared helpers for parsing a large log file's lines concurrently across a worker pool,
+//! used by `AgentAdapter::parse_log_file_parallel` implementations whose `parse_log_line` is
+//! stateless and therefore safe to fan out.
+use crate::grammar::LineGrammar;
+use anyhow::Result;
+use devlog_core::AgentEvent;
+use serde_json::Value;
+use std::ops::Range;
+
+/// Number of workers to use when the caller doesn't specify one: one per available CPU.
+pub fn default_worker_count() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Split `bytes` into roughly `workers` byte ranges, each snapped forward to the next `\n` so
+/// no range ever splits a line in half.
+pub fn line_aligned_chunks(bytes: &[u8], workers: usize) -> Vec<Range<usize>> {
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+
+    let workers = workers.max(1);
+    let target_size = (bytes.len() / workers).max(1);
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        let mut end = (start + target_size).min(bytes.len());
+        if end < bytes.len() {
+            match bytes[end..].iter().position(|&b| b == b'\n') {
+                Some(offset) => end += offset + 1,
+                None => end = bytes.len(),
+            }
+        }
+        ranges.push(start..end);
+        start = end;
+    }
+    ranges
+}
+
+/// Parse `bytes` by splitting it into line-aligned chunks and running `parse_line` over each
+/// chunk's lines concurrently on scoped worker threads, then merging results back in original
+/// order (each chunk tagged with its starting byte offset for a stable merge) and stitching
+/// continuation lines into the preceding event, exactly like the sequential `parse_log_file`
+/// path does via `LineGrammar::is_continuation`. Continuation detection only needs the raw line
+/// text, so it's redone in this single-threaded merge step without losing the parallelism on
+/// the expensive per-line `parse_line` call — a continuation line is never handed to
+/// `parse_line` on the worker threads. The one exception is a continuation-shaped line with no
+/// preceding event to attach to anywhere earlier in the merge (e.g. the very first line of the file):
+/// the sequential path falls through and parses it as a standalone line rather than dropping it,
+/// so the merge step does the same as a rare, single-threaded fallback.
+///
+/// `parse_line` is also handed each line's ordinal — its 0-based line number within `bytes` —
+/// computed from a single cheap linear newline count per chunk before any worker spawns, so two
+/// textually-identical lines at different positions in the file still get distinct ordinals
+/// without the workers needing to coordinate with each other.
+///
+/// A `parse_line` error aborts the whole parse and is propagated to the caller, exactly like the
+/// sequential `parse_log_file` path does via `?` — a line that fails to parse must not silently
+/// vanish from the result just because it happened to land on a worker thread.
+pub fn parse_chunks_parallel<F>(bytes: &[u8], workers: usize, parse_line: F) -> Result<Vec<AgentEvent>>
+where
+    F: Fn(&str, u32) -> Result<Option<AgentEvent>> + Sync,
+{
+    let mut next_ordinal = 0u32;
+    let ranges: Vec<(Range<usize>, u32)> = line_aligned_chunks(bytes, workers)
+        .into_iter()
+        .map(|range| {
+            let starting_ordinal = next_ordinal;
+            next_ordinal += bytes[range.clone()].iter().filter(|&&b| b == b'\n').count() as u32;
+            (range, starting_ordinal)
+        })
+        .collect();
+
+    let mut chunk_results: Vec<(usize, Vec<(String, Result<Option<AgentEvent>>, u32)>)> = std::thread::scope(|scope| {
+        let parse_line = &parse_line;
+        let handles: Vec<_> = ranges
+            .into_iter()
+            .map(|(range, starting_ordinal)| {
+                let start = range.start;
+                let chunk = &bytes[range];
+                scope.spawn(move || {
+                    let results = chunk
+                        .split(|&b| b == b'\n')
+                        .enumerate()
+                        .filter(|(_, line)| !line.is_empty())
+                        .map(|(index, line)| {
+                            let this_ordinal = starting_ordinal.wrapping_add(index as u32);
+                            let line = String::from_utf8_lossy(line).into_owned();
+                            let event = if LineGrammar::is_continuation(&line) {
+                                Ok(None)
+                            } else {
+                                parse_line(&line, this_ordinal)
+                            };
+                            (line, event, this_ordinal)
+                        })
+                        .collect();
+                    (start, results)
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().expect("parse worker panicked")).collect()
+    });
+
+    chunk_results.sort_by_key(|(start, _)| *start);
+
+    let mut events: Vec<AgentEvent> = Vec::new();
+    for (_, results) in chunk_results {
+        for (line, parsed, ordinal) in results {
+            if LineGrammar::is_continuation(&line) {
+                if let Some(last) = events.last_mut() {
+                    if let Some(Value::String(message)) = last.data.get_mut("message") {
+                        message.push('\n');
+                        message.push_str(line.trim());
+                    }
+                    continue;
+                }
+
+                // No prior event in this merge to attach to — fall back to parsing it as a
+                // standalone line instead of silently dropping it, matching `parse_log_file`.
+                if let Some(event) = parse_line(&line, ordinal)? {
+                    events.push(event);
+                }
+                continue;
+            }
+
+            if let Some(event) = parsed? {
+                events.push(event);
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_aligned_chunks_never_split_a_line() {
+        let bytes = b"line one\nline two\nline three\nline four\n";
+        let ranges = line_aligned_chunks(bytes, 3);
+
+        let mut reconstructed = Vec::new();
+        for range in &ranges {
+            reconstructed.extend_from_slice(&bytes[range.clone()]);
+        }
+        assert_eq!(reconstructed, bytes);
+
+        for range in &ranges {
+            if range.end < bytes.len() {
+                assert_eq!(bytes[range.end - 1], b'\n');
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_chunks_parallel_preserves_order() {
+        let bytes = (0..50).map(|i| format!("{i}\n")).collect::<String>();
+        let events = parse_chunks_parallel(bytes.as_bytes(), 8, |line, _ordinal| {
+            Ok(Some(AgentEvent {
+                id: line.to_string(),
+                timestamp: chrono::Utc::now(),
+                event_type: "test".to_string(),
+                agent_id: "test".to_string(),
+                agent_version: "".to_string(),
+                session_id: "".to_string(),
+                project_id: 0,
+                machine_id: None,
+                workspace_id: None,
+                legacy_project_id: None,
+                context: Default::default(),
+                data: Default::default(),
+                metrics: None,
+            }))
+        })
+        .unwrap();
+
+        let ids: Vec<i32> = events.iter().map(|e| e.id.parse().unwrap()).collect();
+        let expected: Vec<i32> = (0..50).collect();
+        assert_eq!(ids, expected);
+    }
+
+    fn make_event(message: &str) -> AgentEvent {
+        let mut data = std::collections::HashMap::new();
+        data.insert("message".to_string(), Value::String(message.to_string()));
+        AgentEvent {
+            id: message.to_string(),
+            timestamp: chrono::Utc::now(),
+            event_type: "test".to_string(),
+            agent_id: "test".to_string(),
+            agent_version: "".to_string(),
+            session_id: "".to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: Default::default(),
+            data,
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_chunks_parallel_stitches_continuation_lines() {
+        let bytes = b"[2025-10-31 10:00:00] INFO tool call\n    stack trace line 1\n    stack trace line 2\n[2025-10-31 10:00:01] INFO another tool call\n";
+        let events = parse_chunks_parallel(bytes, 4, |line, _ordinal| Ok(Some(make_event(line)))).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].data.get("message").unwrap(),
+            &Value::String("[2025-10-31 10:00:00] INFO tool call\nstack trace line 1\nstack trace line 2".to_string())
+        );
+        assert_eq!(
+            events[1].data.get("message").unwrap(),
+            &Value::String("[2025-10-31 10:00:01] INFO another tool call".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_chunks_parallel_propagates_parse_line_errors() {
+        let bytes = b"good line\nbad line\nanother good line\n";
+        let result = parse_chunks_parallel(bytes, 1, |line, _ordinal| {
+            if line == "bad line" {
+                anyhow::bail!("boom");
+            }
+            Ok(Some(make_event(line)))
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_chunks_parallel_parses_leading_continuation_shaped_line() {
+        // A continuation-shaped line with nothing preceding it (e.g. a log that starts with an
+        // indented line) has no event to attach to, so it must still surface as its own event
+        // rather than being dropped.
+        let bytes = b"    orphaned indented line\n[2025-10-31 10:00:00] INFO tool call\n";
+        let events = parse_chunks_parallel(bytes, 1, |line, _ordinal| Ok(Some(make_event(line)))).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].data.get("message").unwrap(),
+            &Value::String("    orphaned indented line".to_string())
+        );
+    }
+}
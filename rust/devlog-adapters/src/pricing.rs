@@ -0,0 +1,132 @@
+//! Per-model token pricing used by adapters to turn prompt/response token counts into a
+//! dollar `cost` figure on `EventMetrics`.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Per-1K-token input/output rates for a model name pattern. `model_pattern` supports a
+/// trailing `*` glob (e.g. `claude-3-5-sonnet*`, `gpt-4*`) so a family of model ids can share
+/// one entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRate {
+    pub model_pattern: String,
+    pub input_rate_per_1k: f64,
+    pub output_rate_per_1k: f64,
+}
+
+/// A pluggable table of model pricing, checked in order so more specific patterns can be
+/// listed before broader fallbacks.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PricingTable {
+    rates: Vec<ModelRate>,
+}
+
+impl PricingTable {
+    pub fn new(rates: Vec<ModelRate>) -> Self {
+        Self { rates }
+    }
+
+    pub fn from_toml_str(s: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(s)?)
+    }
+
+    pub fn from_json_str(s: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json_str(&contents),
+            _ => Self::from_toml_str(&contents),
+        }
+    }
+
+    /// Sane defaults for common Cursor/Claude models, so `cost` is populated out of the box.
+    pub fn with_defaults() -> Self {
+        Self::new(vec![
+            ModelRate {
+                model_pattern: "claude-3-5-sonnet*".to_string(),
+                input_rate_per_1k: 0.003,
+                output_rate_per_1k: 0.015,
+            },
+            ModelRate {
+                model_pattern: "claude-3-opus*".to_string(),
+                input_rate_per_1k: 0.015,
+                output_rate_per_1k: 0.075,
+            },
+            ModelRate {
+                model_pattern: "claude-3-haiku*".to_string(),
+                input_rate_per_1k: 0.00025,
+                output_rate_per_1k: 0.00125,
+            },
+            ModelRate {
+                model_pattern: "gpt-4o*".to_string(),
+                input_rate_per_1k: 0.005,
+                output_rate_per_1k: 0.015,
+            },
+            ModelRate {
+                model_pattern: "gpt-4*".to_string(),
+                input_rate_per_1k: 0.03,
+                output_rate_per_1k: 0.06,
+            },
+            ModelRate {
+                model_pattern: "gpt-3.5*".to_string(),
+                input_rate_per_1k: 0.0005,
+                output_rate_per_1k: 0.0015,
+            },
+        ])
+    }
+
+    fn matches(pattern: &str, model: &str) -> bool {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => model.starts_with(prefix),
+            None => pattern == model,
+        }
+    }
+
+    /// Resolve the rate for `model`, preferring the first matching entry.
+    pub fn rate_for(&self, model: &str) -> Option<&ModelRate> {
+        self.rates.iter().find(|r| Self::matches(&r.model_pattern, model))
+    }
+
+    /// Compute `cost` for the given token counts, or `None` if the model is unknown or a
+    /// token count is missing.
+    pub fn cost(&self, model: Option<&str>, prompt_tokens: Option<i32>, response_tokens: Option<i32>) -> Option<f64> {
+        let model = model?;
+        let rate = self.rate_for(model)?;
+        let prompt_tokens = prompt_tokens?;
+        let response_tokens = response_tokens?;
+
+        Some(
+            (prompt_tokens as f64 / 1000.0) * rate.input_rate_per_1k
+                + (response_tokens as f64 / 1000.0) * rate.output_rate_per_1k,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match_prefers_specific_entry() {
+        let table = PricingTable::with_defaults();
+        let rate = table.rate_for("claude-3-5-sonnet-20241022").unwrap();
+        assert_eq!(rate.model_pattern, "claude-3-5-sonnet*");
+    }
+
+    #[test]
+    fn test_cost_computation() {
+        let table = PricingTable::with_defaults();
+        let cost = table.cost(Some("gpt-4-turbo"), Some(1000), Some(500)).unwrap();
+        assert!((cost - (0.03 + 0.03)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unknown_model_has_no_cost() {
+        let table = PricingTable::with_defaults();
+        assert_eq!(table.cost(Some("some-unreleased-model"), Some(100), Some(100)), None);
+        assert_eq!(table.cost(None, Some(100), Some(100)), None);
+        assert_eq!(table.cost(Some("gpt-4"), None, Some(100)), None);
+    }
+}
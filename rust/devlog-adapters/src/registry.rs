@@ -1,6 +1,14 @@
 use crate::AgentAdapter;
+use anyhow::{anyhow, Result};
+use devlog_core::AgentEvent;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Number of leading lines sampled from a file to run content-based detection against.
+const DETECTION_SAMPLE_LINES: usize = 10;
 
 pub struct Registry {
     adapters: HashMap<String, Arc<dyn AgentAdapter>>,
@@ -24,6 +32,77 @@ impl Registry {
     pub fn list(&self) -> Vec<Arc<dyn AgentAdapter>> {
         self.adapters.values().cloned().collect()
     }
+
+    /// Rank every registered adapter's confidence that `sample` belongs to its format, most
+    /// confident first. Adapters scoring `0.0` are excluded.
+    pub fn detect_all(&self, sample: &str) -> Vec<(Arc<dyn AgentAdapter>, f64)> {
+        let mut scored: Vec<(Arc<dyn AgentAdapter>, f64)> = self
+            .adapters
+            .values()
+            .map(|adapter| (adapter.clone(), adapter.detection_confidence(sample)))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored
+    }
+
+    /// The single best-matching adapter for `sample`, if any adapter scored above `0.0`.
+    pub fn detect(&self, sample: &str) -> Option<Arc<dyn AgentAdapter>> {
+        self.detect_all(sample).into_iter().next().map(|(adapter, _)| adapter)
+    }
+
+    /// Sample the first few non-empty lines of `path`, score every registered adapter against
+    /// each sample, and parse the whole file with whichever adapter scored highest overall —
+    /// not just whichever matched the first sample, since one ambiguous leading line shouldn't
+    /// outweigh the rest of the file agreeing on a different format.
+    pub async fn parse_file_auto(&self, path: &Path) -> Result<Vec<AgentEvent>> {
+        let samples = Self::read_samples(path).await?;
+        let adapter = self
+            .detect_across(&samples)
+            .ok_or_else(|| anyhow!("no registered adapter matched file: {}", path.display()))?;
+
+        adapter.parse_log_file(path).await
+    }
+
+    /// The adapter with the highest mean `detection_confidence` across all of `samples`, if any
+    /// adapter scored above `0.0` on average.
+    fn detect_across(&self, samples: &[String]) -> Option<Arc<dyn AgentAdapter>> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        self.adapters
+            .values()
+            .map(|adapter| {
+                let total: f64 = samples.iter().map(|sample| adapter.detection_confidence(sample)).sum();
+                (adapter.clone(), total / samples.len() as f64)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(adapter, _)| adapter)
+    }
+
+    /// Read up to `DETECTION_SAMPLE_LINES` non-empty lines, each a detection candidate on its
+    /// own (most log formats are one JSON object or one plain-text entry per line).
+    async fn read_samples(path: &Path) -> Result<Vec<String>> {
+        let file = File::open(path).await?;
+        let mut lines = BufReader::new(file).lines();
+
+        let mut samples = Vec::new();
+        while samples.len() < DETECTION_SAMPLE_LINES {
+            match lines.next_line().await? {
+                Some(line) => {
+                    if !line.trim().is_empty() {
+                        samples.push(line);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Ok(samples)
+    }
 }
 
 impl Default for Registry {
@@ -31,3 +110,51 @@ impl Default for Registry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude::ClaudeAdapter;
+    use crate::cursor::CursorAdapter;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_detect_picks_the_strongest_scoring_adapter() {
+        let mut registry = Registry::new();
+        registry.register(Arc::new(ClaudeAdapter::new("test-project".to_string())));
+        registry.register(Arc::new(CursorAdapter::new("test-project".to_string())));
+
+        let sample = r#"{"timestamp":"2025-10-31T10:00:00Z","conversation_id":"conv_1","model":"claude-3-5-sonnet","message":"claude request"}"#;
+        let adapter = registry.detect(sample).unwrap();
+        assert_eq!(adapter.name(), "claude");
+    }
+
+    #[test]
+    fn test_detect_all_excludes_non_matching_adapters() {
+        let mut registry = Registry::new();
+        registry.register(Arc::new(ClaudeAdapter::new("test-project".to_string())));
+
+        assert!(registry.detect_all("not json at all").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_parse_file_auto_does_not_let_one_ambiguous_line_decide_the_winner() {
+        let mut registry = Registry::new();
+        registry.register(Arc::new(ClaudeAdapter::new("test-project".to_string())));
+        registry.register(Arc::new(CursorAdapter::new("test-project".to_string())));
+
+        // First line is a Claude-looking entry with no ids or model (Claude scores it above
+        // Cursor's generic-JSON score on its own); every remaining line is an unambiguous Cursor
+        // entry. The overall winner should be Cursor, not whichever adapter won line one.
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"timestamp":"2025-10-31T10:00:00Z","message":"claude log line"}}"#).unwrap();
+        for _ in 0..5 {
+            writeln!(file, r#"{{"session_id":"sess_1","model":"gpt-4","message":"cursor completion"}}"#).unwrap();
+        }
+
+        let samples = Registry::read_samples(file.path()).await.unwrap();
+        let adapter = registry.detect_across(&samples).unwrap();
+        assert_eq!(adapter.name(), "cursor");
+    }
+}
@@ -0,0 +1,277 @@
+//! Per-model byte-level BPE tokenizer, used to turn prompt/response text into the token counts
+//! providers actually bill for, rather than a word-count approximation.
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Something that can estimate how many tokens a provider would bill `text` as.
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> i32;
+}
+
+/// The original `words * 1.3` approximation, kept as the fallback for a `model_id` with no
+/// registered BPE vocabulary.
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count_tokens(&self, text: &str) -> i32 {
+        (text.split_whitespace().count() as f64 * 1.3) as i32
+    }
+}
+
+/// Token id returned by [`BpeTokenizer::token_id`] for a piece the loaded vocabulary has no
+/// entry for (can only happen with a caller-supplied vocab that doesn't cover every merge it
+/// was built with; `BpeTokenizer::with_merges`'s derived vocab always covers its own merges).
+pub const UNKNOWN_TOKEN_ID: u32 = u32::MAX;
+
+fn pretokenizer() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+")
+            .expect("pretokenizer regex is valid")
+    })
+}
+
+/// Maps a raw byte to a single `char` in the Unicode Private Use Area, so merges can operate on
+/// `String`s of these symbols rather than juggling raw byte slices.
+fn byte_symbol(b: u8) -> char {
+    char::from_u32(0xE000 + b as u32).expect("0xE000..0xE100 is a valid Private Use Area range")
+}
+
+/// Token ids `0..256` for the raw byte symbols, plus one id per merge in `merges` (assigned in
+/// merge order, after the byte range), the standard way a BPE vocabulary is built up from a
+/// merge list when no separately-trained vocab file is available.
+fn vocab_from_merges(merges: &[(String, String)]) -> HashMap<String, u32> {
+    let mut vocab: HashMap<String, u32> = (0u32..256).map(|b| (byte_symbol(b as u8).to_string(), b)).collect();
+    for (rank, (left, right)) in merges.iter().enumerate() {
+        vocab.entry(format!("{}{}", left, right)).or_insert(256 + rank as u32);
+    }
+    vocab
+}
+
+/// Byte-level BPE: pre-tokenize `text` with a GPT-style regex, UTF-8-encode each pre-token to
+/// bytes, then repeatedly merge the adjacent pair with the lowest merge rank until none of the
+/// loaded merges apply. Token count is the number of resulting pieces summed across pre-tokens.
+pub struct BpeTokenizer {
+    vocab: HashMap<String, u32>,
+    merge_ranks: HashMap<(String, String), usize>,
+}
+
+impl BpeTokenizer {
+    /// Build from an explicit vocabulary (token -> id) and an ordered merge list (earlier pairs
+    /// merge first) — the two artifacts a real provider's BPE tokenizer ships as.
+    pub fn new(vocab: HashMap<String, u32>, merges: Vec<(String, String)>) -> Self {
+        let merge_ranks = merges.into_iter().enumerate().map(|(rank, pair)| (pair, rank)).collect();
+        Self { vocab, merge_ranks }
+    }
+
+    /// Build from just a merge list, deriving the vocabulary from it (see [`vocab_from_merges`]),
+    /// for callers that only have a merge table and not a separately-trained vocab file.
+    pub fn with_merges(merges: Vec<(String, String)>) -> Self {
+        let vocab = vocab_from_merges(&merges);
+        Self::new(vocab, merges)
+    }
+
+    /// A small built-in merge table covering common English/code bigrams, used when no
+    /// model-specific vocabulary has been registered. Not a substitute for a provider's real
+    /// vocabulary, but far closer to real token counts than a word-count heuristic.
+    pub fn with_defaults() -> Self {
+        Self::with_merges(base_merges())
+    }
+
+    /// Look up the vocabulary id for an already-merged piece, or [`UNKNOWN_TOKEN_ID`] if the
+    /// loaded vocabulary has no entry for it.
+    pub fn token_id(&self, piece: &str) -> u32 {
+        self.vocab.get(piece).copied().unwrap_or(UNKNOWN_TOKEN_ID)
+    }
+
+    /// Encode `text` to the vocabulary ids of its merged pieces, in order.
+    pub fn encode(&self, text: &str) -> Vec<u32> {
+        pretokenizer()
+            .find_iter(text)
+            .flat_map(|m| self.encode_pretoken(m.as_str()))
+            .map(|piece| self.token_id(&piece))
+            .collect()
+    }
+
+    fn encode_pretoken(&self, pretoken: &str) -> Vec<String> {
+        let mut symbols: Vec<String> = pretoken.bytes().map(|b| byte_symbol(b).to_string()).collect();
+
+        while symbols.len() > 1 {
+            let best = (0..symbols.len() - 1)
+                .filter_map(|i| {
+                    self.merge_ranks
+                        .get(&(symbols[i].clone(), symbols[i + 1].clone()))
+                        .map(|&rank| (rank, i))
+                })
+                .min();
+
+            let Some((_, i)) = best else { break };
+            let merged = format!("{}{}", symbols[i], symbols[i + 1]);
+            symbols.splice(i..=i + 1, [merged]);
+        }
+
+        symbols
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn count_tokens(&self, text: &str) -> i32 {
+        pretokenizer()
+            .find_iter(text)
+            .map(|m| self.encode_pretoken(m.as_str()).len())
+            .sum::<usize>() as i32
+    }
+}
+
+/// Hand-picked common English/code bigrams, roughly ordered by frequency, shared as the base
+/// that every built-in per-model merge table layers its own additions on top of.
+fn base_merges() -> Vec<(String, String)> {
+    [
+        "th", "he", "in", "er", "an", "re", "on", "at", "en", "nd", "is", "or", "it", "ti", "es",
+        "te", "st", "ar", "to", "nt", "le", "ha", "se", "ve", "co", "me", "de", "ro", "la", "di",
+    ]
+    .iter()
+    .map(|pair| {
+        let mut chars = pair.chars();
+        let left = byte_symbol(chars.next().unwrap() as u8).to_string();
+        let right = byte_symbol(chars.next().unwrap() as u8).to_string();
+        (left, right)
+    })
+    .collect()
+}
+
+/// `base_merges()` plus a few extra bigrams, applied at a lower (higher-priority) rank than the
+/// base table, so a family's tokenizer merges its own common pieces first. Still a hand-picked
+/// approximation, not a trained vocabulary, but distinct per family rather than one table shared
+/// by every model.
+fn family_merges(extra_bigrams_highest_priority: &[&str]) -> Vec<(String, String)> {
+    let extra = extra_bigrams_highest_priority.iter().map(|pair| {
+        let mut chars = pair.chars();
+        let left = byte_symbol(chars.next().unwrap() as u8).to_string();
+        let right = byte_symbol(chars.next().unwrap() as u8).to_string();
+        (left, right)
+    });
+    extra.chain(base_merges()).collect()
+}
+
+/// One entry in a [`TokenizerRegistry`]: the `model_id` glob pattern it applies to, plus the
+/// vocabulary and merge list to build its `BpeTokenizer` from.
+pub struct TokenizerSource {
+    pub model_pattern: String,
+    pub vocab: HashMap<String, u32>,
+    pub merges: Vec<(String, String)>,
+}
+
+impl TokenizerSource {
+    pub fn new(model_pattern: impl Into<String>, vocab: HashMap<String, u32>, merges: Vec<(String, String)>) -> Self {
+        Self { model_pattern: model_pattern.into(), vocab, merges }
+    }
+
+    /// Convenience constructor that derives the vocab from `merges` (see [`vocab_from_merges`])
+    /// rather than requiring a separately-loaded vocab file.
+    pub fn from_merges(model_pattern: impl Into<String>, merges: Vec<(String, String)>) -> Self {
+        let vocab = vocab_from_merges(&merges);
+        Self::new(model_pattern, vocab, merges)
+    }
+}
+
+/// Tokenizers keyed by `model_id` glob pattern (same convention as `PricingTable::model_pattern`:
+/// an optional trailing `*`), resolved and cached lazily so a given model's vocabulary and merge
+/// table are only built once.
+pub struct TokenizerRegistry {
+    sources: Vec<TokenizerSource>,
+    cache: Mutex<HashMap<String, Arc<dyn Tokenizer>>>,
+}
+
+impl TokenizerRegistry {
+    pub fn new(sources: Vec<TokenizerSource>) -> Self {
+        Self { sources, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Per-model-family merge tables (and their derived vocabularies) so out of the box each
+    /// provider family gets its own approximation rather than one generic table for every model.
+    /// Still hand-picked bigrams, not a trained vocabulary — real provider vocab files can be
+    /// loaded via [`TokenizerSource::new`] to replace any of these.
+    pub fn with_defaults() -> Self {
+        Self::new(vec![
+            TokenizerSource::from_merges("claude-*", family_merges(&["ai", "ss"])),
+            TokenizerSource::from_merges("gpt-*", family_merges(&["ng", "io"])),
+            TokenizerSource::from_merges("gemini-*", family_merges(&["mi", "ni"])),
+            TokenizerSource::from_merges("*", base_merges()),
+        ])
+    }
+
+    fn matches(pattern: &str, model: &str) -> bool {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => model.starts_with(prefix),
+            None => pattern == model,
+        }
+    }
+
+    /// Resolve (and cache) the tokenizer for `model_id`, falling back to the word-count
+    /// heuristic when no registered vocabulary matches.
+    pub fn for_model(&self, model_id: &str) -> Arc<dyn Tokenizer> {
+        if let Some(cached) = self.cache.lock().unwrap().get(model_id) {
+            return cached.clone();
+        }
+
+        let tokenizer: Arc<dyn Tokenizer> = match self.sources.iter().find(|source| Self::matches(&source.model_pattern, model_id)) {
+            Some(source) => Arc::new(BpeTokenizer::new(source.vocab.clone(), source.merges.clone())),
+            None => Arc::new(HeuristicTokenizer),
+        };
+
+        self.cache.lock().unwrap().insert(model_id.to_string(), tokenizer.clone());
+        tokenizer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bpe_merges_known_bigrams() {
+        let tokenizer = BpeTokenizer::with_defaults();
+        // "the" byte-pretokenizes to one piece; "th" and "he" are both registered bigrams, so
+        // "t","h","e" merges down to 2 pieces ("th","e") rather than staying at 3.
+        assert_eq!(tokenizer.encode_pretoken("the").len(), 2);
+    }
+
+    #[test]
+    fn test_bpe_vocab_resolves_ids_for_its_own_merges() {
+        let tokenizer = BpeTokenizer::with_defaults();
+        let pieces = tokenizer.encode_pretoken("the");
+        for piece in pieces {
+            assert_ne!(tokenizer.token_id(&piece), UNKNOWN_TOKEN_ID);
+        }
+    }
+
+    #[test]
+    fn test_heuristic_fallback_for_unknown_model() {
+        let registry = TokenizerRegistry::new(vec![TokenizerSource::from_merges("claude-*", vec![])]);
+        let tokenizer = registry.for_model("gpt-4o");
+        assert_eq!(tokenizer.count_tokens("hello world"), 2);
+    }
+
+    #[test]
+    fn test_registry_caches_resolved_tokenizer() {
+        let registry = TokenizerRegistry::with_defaults();
+        let first = registry.for_model("gpt-4o");
+        let second = registry.for_model("gpt-4o");
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn test_registry_resolves_distinct_tokenizers_per_model_family() {
+        let registry = TokenizerRegistry::with_defaults();
+        let claude = registry.for_model("claude-3-5-sonnet-20241022");
+        let gpt = registry.for_model("gpt-4o");
+
+        // Each family layers its own extra bigrams ahead of the shared base table, so the same
+        // text can merge down to a different piece count depending on which family resolved:
+        // claude's extra "ss" bigram merges "classic"'s double-s, gpt's "ng"/"io" don't apply.
+        let sample = "classic";
+        assert_ne!(claude.count_tokens(sample), gpt.count_tokens(sample));
+    }
+}
@@ -0,0 +1,273 @@
+//! Optional ClickHouse export: mirrors ingested `AgentEvent`s into a flattened analytics table in
+//! the background, so per-agent/per-project cost and latency can be queried across thousands of
+//! sessions without turning the local SQLite ring buffer into a long-term store it was never sized
+//! for. Wired into `AppState` as `Option<Arc<Sink>>`, so it's simply absent when no
+//! `analytics.dsn` is configured.
+use anyhow::{Context, Result};
+use clickhouse::{Client, Row};
+use devlog_core::AgentEvent;
+use log::{error, warn};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Events accepted onto the export channel before [`Sink::enqueue`] starts dropping them. Sized
+/// well above `batch_max_events` so a slow or momentarily-unreachable ClickHouse server doesn't
+/// immediately shed load under normal batch-interval jitter.
+const CHANNEL_CAPACITY: usize = 10_000;
+
+pub struct Config {
+    pub dsn: String,
+    pub database: String,
+    pub table: String,
+    pub batch_max_events: usize,
+    pub batch_max_interval_ms: u64,
+    pub max_retries: u32,
+}
+
+/// One row of the flattened `AgentEvent`/`EventMetrics` schema ClickHouse stores. `context`/`data`
+/// stay as JSON strings rather than native columns since their shape varies per adapter.
+#[derive(Debug, Clone, Serialize, Row)]
+pub struct AnalyticsRow {
+    pub event_id: String,
+    pub timestamp: i64,
+    pub event_type: String,
+    pub agent_id: String,
+    pub agent_version: String,
+    pub session_id: String,
+    pub project_id: i32,
+    pub token_count: Option<i32>,
+    pub prompt_tokens: Option<i32>,
+    pub response_tokens: Option<i32>,
+    pub duration_ms: Option<i64>,
+    pub cost: Option<f64>,
+    pub context: String,
+    pub data: String,
+}
+
+impl AnalyticsRow {
+    fn from_event(event: &AgentEvent) -> Self {
+        let metrics = event.metrics.clone().unwrap_or_default();
+        Self {
+            event_id: event.id.clone(),
+            timestamp: event.timestamp.timestamp(),
+            event_type: event.event_type.clone(),
+            agent_id: event.agent_id.clone(),
+            agent_version: event.agent_version.clone(),
+            session_id: event.session_id.clone(),
+            project_id: event.project_id,
+            token_count: metrics.token_count,
+            prompt_tokens: metrics.prompt_tokens,
+            response_tokens: metrics.response_tokens,
+            duration_ms: metrics.duration_ms,
+            cost: metrics.cost,
+            context: serde_json::to_string(&event.context).unwrap_or_default(),
+            data: serde_json::to_string(&event.data).unwrap_or_default(),
+        }
+    }
+}
+
+fn create_table_sql(table: &str) -> String {
+    format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {table} (
+            event_id String,
+            timestamp DateTime,
+            event_type String,
+            agent_id String,
+            agent_version String,
+            session_id String,
+            project_id Int32,
+            token_count Nullable(Int32),
+            prompt_tokens Nullable(Int32),
+            response_tokens Nullable(Int32),
+            duration_ms Nullable(Int64),
+            cost Nullable(Float64),
+            context String,
+            data String
+        )
+        ENGINE = MergeTree()
+        PARTITION BY toDate(timestamp)
+        ORDER BY (project_id, agent_id, timestamp)
+        "#
+    )
+}
+
+/// Background ClickHouse export sink. Feeding it never blocks or fails ingestion: a full or
+/// closed channel just drops the event from analytics, since the SQLite buffer (or whichever
+/// `EventStore` is configured) remains the durable source of truth.
+pub struct Sink {
+    tx: mpsc::Sender<AgentEvent>,
+}
+
+impl Sink {
+    pub fn new(config: Config) -> Result<Self> {
+        let client = Client::default()
+            .with_url(&config.dsn)
+            .with_database(&config.database);
+
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(run_flush_loop(rx, client, config));
+
+        Ok(Self { tx })
+    }
+
+    /// Enqueue `event` for export. See the struct-level note on why this never surfaces an error.
+    pub fn enqueue(&self, event: &AgentEvent) {
+        if self.tx.try_send(event.clone()).is_err() {
+            warn!("analytics channel full or closed, dropping event {} from ClickHouse export", event.id);
+        }
+    }
+}
+
+async fn run_flush_loop(mut rx: mpsc::Receiver<AgentEvent>, client: Client, config: Config) {
+    if let Err(e) = client.query(&create_table_sql(&config.table)).execute().await {
+        warn!("failed to ensure ClickHouse analytics table exists, inserts will retry schema creation implicitly: {}", e);
+    }
+
+    let mut batch = Vec::with_capacity(config.batch_max_events);
+    let mut interval = tokio::time::interval(Duration::from_millis(config.batch_max_interval_ms));
+    interval.tick().await; // first tick fires immediately; skip it so an idle start doesn't flush an empty batch
+
+    loop {
+        tokio::select! {
+            received = rx.recv() => {
+                match received {
+                    Some(event) => {
+                        batch.push(event);
+                        if batch.len() >= config.batch_max_events {
+                            flush(&client, &config, &mut batch).await;
+                        }
+                    }
+                    None => {
+                        flush(&client, &config, &mut batch).await;
+                        break;
+                    }
+                }
+            }
+            _ = interval.tick() => {
+                if !batch.is_empty() {
+                    flush(&client, &config, &mut batch).await;
+                }
+            }
+        }
+    }
+}
+
+/// Insert `batch` into ClickHouse, retrying with exponential backoff up to `config.max_retries`
+/// times. A batch that still fails after every retry is logged and dropped from analytics only —
+/// the events it covered are never removed from the local buffer, so nothing is lost.
+async fn flush(client: &Client, config: &Config, batch: &mut Vec<AgentEvent>) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let rows: Vec<AnalyticsRow> = batch.iter().map(AnalyticsRow::from_event).collect();
+    let mut attempt = 0;
+
+    loop {
+        match insert_batch(client, &config.table, &rows).await {
+            Ok(()) => break,
+            Err(e) if attempt < config.max_retries => {
+                let backoff = Duration::from_millis(100 * 2u64.saturating_pow(attempt));
+                warn!(
+                    "ClickHouse insert failed (attempt {}/{}), retrying in {:?}: {}",
+                    attempt + 1,
+                    config.max_retries,
+                    backoff,
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                error!(
+                    "ClickHouse insert failed after {} attempts, leaving {} events in the local buffer only: {}",
+                    attempt + 1,
+                    rows.len(),
+                    e
+                );
+                break;
+            }
+        }
+    }
+
+    batch.clear();
+}
+
+async fn insert_batch(client: &Client, table: &str, rows: &[AnalyticsRow]) -> Result<()> {
+    let mut insert = client.insert(table).context("failed to open ClickHouse insert")?;
+    for row in rows {
+        insert.write(row).await.context("failed to write row to ClickHouse insert")?;
+    }
+    insert.end().await.context("failed to finalize ClickHouse insert")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use devlog_core::{EventMetrics, EVENT_TYPE_LLM_RESPONSE};
+    use std::collections::HashMap;
+
+    fn sample_event() -> AgentEvent {
+        AgentEvent {
+            id: "evt-1".to_string(),
+            timestamp: chrono::Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            event_type: EVENT_TYPE_LLM_RESPONSE.to_string(),
+            agent_id: "claude".to_string(),
+            agent_version: "1.0".to_string(),
+            session_id: "sess-1".to_string(),
+            project_id: 3,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::from([("model".to_string(), serde_json::json!("gpt-4"))]),
+            data: HashMap::from([("toolName".to_string(), serde_json::json!("bash"))]),
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_from_event_defaults_metric_columns_to_none_when_event_has_no_metrics() {
+        let row = AnalyticsRow::from_event(&sample_event());
+        assert_eq!(row.event_id, "evt-1");
+        assert_eq!(row.timestamp, 1_700_000_000);
+        assert_eq!(row.token_count, None);
+        assert_eq!(row.prompt_tokens, None);
+        assert_eq!(row.response_tokens, None);
+        assert_eq!(row.duration_ms, None);
+        assert_eq!(row.cost, None);
+    }
+
+    #[test]
+    fn test_from_event_carries_metric_fields_through_as_typed_columns() {
+        let mut event = sample_event();
+        event.metrics = Some(EventMetrics {
+            token_count: Some(150),
+            prompt_tokens: Some(100),
+            response_tokens: Some(50),
+            duration_ms: Some(820),
+            cost: Some(0.0031),
+        });
+
+        let row = AnalyticsRow::from_event(&event);
+        assert_eq!(row.token_count, Some(150));
+        assert_eq!(row.prompt_tokens, Some(100));
+        assert_eq!(row.response_tokens, Some(50));
+        assert_eq!(row.duration_ms, Some(820));
+        assert_eq!(row.cost, Some(0.0031));
+    }
+
+    #[test]
+    fn test_from_event_serializes_context_and_data_as_json_strings() {
+        let row = AnalyticsRow::from_event(&sample_event());
+
+        let context: serde_json::Value = serde_json::from_str(&row.context).unwrap();
+        assert_eq!(context["model"], "gpt-4");
+
+        let data: serde_json::Value = serde_json::from_str(&row.data).unwrap();
+        assert_eq!(data["toolName"], "bash");
+    }
+}
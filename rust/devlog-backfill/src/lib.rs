@@ -1,8 +1,10 @@
 use devlog_core::AgentEvent;
 use devlog_adapters::{Registry, AgentAdapter};
-use devlog_buffer::Buffer;
+use devlog_buffer::{EventStore, SqliteStore};
 use sqlx::{sqlite::SqlitePool, Row};
 use anyhow::{Result, Context, anyhow};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc, TimeZone};
 use std::sync::Arc;
@@ -11,12 +13,74 @@ use tokio::fs::File;
 use tokio::io::{AsyncBufReadExt, BufReader, AsyncSeekExt};
 use walkdir::WalkDir;
 
+/// How many leading bytes of a file to hash for [`fingerprint_file`]'s portable fallback.
+const FINGERPRINT_SAMPLE_BYTES: usize = 4096;
+
+/// An identity fingerprint for `file_path` that changes when the file is replaced with different
+/// content at the same path. On Unix it is the on-disk device/inode, which stays stable across
+/// simple appends (so a growing log doesn't look "rotated" on every run). Elsewhere it is a hash
+/// of the file's first `FINGERPRINT_SAMPLE_BYTES` bytes, which stays stable across appends only
+/// once the file is at least that long; appending to a shorter file changes it.
+fn fingerprint_file(file_path: &Path) -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = std::fs::metadata(file_path)?;
+        Ok(format!("{}:{}", metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        use std::io::Read;
+        let mut file = std::fs::File::open(file_path)?;
+        let mut buf = [0u8; FINGERPRINT_SAMPLE_BYTES];
+        let read = file.read(&mut buf)?;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        buf[..read].hash(&mut hasher);
+        Ok(format!("{:016x}", hasher.finish()))
+    }
+}
+
+/// A stable digest over the fields that identify a logically distinct event, used to skip
+/// re-storing events a previous backfill run already ingested.
+fn event_digest(event: &AgentEvent) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    event.id.hash(&mut hasher);
+    event.timestamp.timestamp_nanos_opt().unwrap_or(0).hash(&mut hasher);
+    event.event_type.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
 pub struct BackfillManager {
     registry: Arc<Registry>,
-    buffer: Arc<Buffer>,
+    buffer: Arc<SqliteStore>,
     pool: SqlitePool,
 }
 
+/// Cooperative stop signal threaded into `backfill_file_*`. Checked between batches so a
+/// `backfill pause` (which flips the row's `status` to `Paused` in `backfill_state`, possibly
+/// from another process sharing this sqlite db) causes an in-flight run to checkpoint and return
+/// cleanly at its next batch boundary, rather than continuing until EOF.
+pub struct CancellationToken {
+    cancelled: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl CancellationToken {
+    fn new() -> Self {
+        Self {
+            cancelled: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BackfillStatus {
     New,
@@ -56,6 +120,7 @@ pub struct BackfillState {
     pub agent_name: String,
     pub log_file_path: String,
     pub last_byte_offset: i64,
+    pub fingerprint: Option<String>,
     pub last_timestamp: Option<DateTime<Utc>>,
     pub total_events_processed: i32,
     pub status: BackfillStatus,
@@ -66,7 +131,7 @@ pub struct BackfillState {
 
 pub struct Config {
     pub registry: Arc<Registry>,
-    pub buffer: Arc<Buffer>,
+    pub buffer: Arc<SqliteStore>,
     pub db_path: String,
 }
 
@@ -100,6 +165,7 @@ impl BackfillManager {
                 agent_name TEXT NOT NULL,
                 log_file_path TEXT NOT NULL,
                 last_byte_offset INTEGER NOT NULL DEFAULT 0,
+                fingerprint TEXT,
                 last_timestamp INTEGER,
                 total_events_processed INTEGER NOT NULL DEFAULT 0,
                 status TEXT NOT NULL DEFAULT 'new',
@@ -110,6 +176,11 @@ impl BackfillManager {
             );
             CREATE INDEX IF NOT EXISTS idx_backfill_status ON backfill_state(status);
             CREATE INDEX IF NOT EXISTS idx_backfill_agent ON backfill_state(agent_name);
+
+            CREATE TABLE IF NOT EXISTS event_digests (
+                digest TEXT PRIMARY KEY,
+                created_at INTEGER NOT NULL
+            );
             "#
         )
         .execute(&self.pool)
@@ -130,6 +201,78 @@ impl BackfillManager {
         Ok(())
     }
 
+    /// List tracked backfill rows, optionally scoped to a single agent, for `backfill status`.
+    pub async fn status(&self, agent_name: Option<&str>) -> Result<Vec<BackfillState>> {
+        let rows = if let Some(agent_name) = agent_name {
+            sqlx::query(
+                "SELECT id, agent_name, log_file_path, last_byte_offset, fingerprint, last_timestamp, total_events_processed, status, started_at, completed_at, error_message FROM backfill_state WHERE agent_name = ? ORDER BY agent_name, log_file_path"
+            )
+            .bind(agent_name)
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            sqlx::query(
+                "SELECT id, agent_name, log_file_path, last_byte_offset, fingerprint, last_timestamp, total_events_processed, status, started_at, completed_at, error_message FROM backfill_state ORDER BY agent_name, log_file_path"
+            )
+            .fetch_all(&self.pool)
+            .await?
+        };
+
+        Ok(rows
+            .into_iter()
+            .map(|row| BackfillState {
+                id: row.get(0),
+                agent_name: row.get(1),
+                log_file_path: row.get(2),
+                last_byte_offset: row.get(3),
+                fingerprint: row.get(4),
+                last_timestamp: row.get::<Option<i64>, _>(5).map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+                total_events_processed: row.get(6),
+                status: BackfillStatus::from(row.get::<String, _>(7)),
+                started_at: Utc.timestamp_opt(row.get(8), 0).unwrap(),
+                completed_at: row.get::<Option<i64>, _>(9).map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+                error_message: row.get(10),
+            })
+            .collect())
+    }
+
+    /// Flip a tracked file to `Paused`. An in-flight `backfill_file_*` loop for that row (in this
+    /// process or a concurrently running one sharing this db) picks this up at its next batch
+    /// boundary and stops cleanly, leaving the saved offset intact for `resume`.
+    pub async fn pause(&self, agent_name: &str, file_path: &Path) -> Result<()> {
+        let path_str = file_path.to_string_lossy().to_string();
+        let result = sqlx::query("UPDATE backfill_state SET status = ? WHERE agent_name = ? AND log_file_path = ?")
+            .bind(BackfillStatus::Paused.to_string())
+            .bind(agent_name)
+            .bind(&path_str)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("no backfill state found for {} {}", agent_name, path_str));
+        }
+
+        Ok(())
+    }
+
+    /// Flip a `Paused`/`Failed` file back to resumable and continue the backfill from its saved
+    /// byte offset.
+    pub async fn resume(&self, agent_name: &str, file_path: &Path, batch_size: usize) -> Result<()> {
+        let state = self.load_state(agent_name, file_path).await?;
+        if state.id == 0 {
+            return Err(anyhow!("no backfill state found for {} {}", agent_name, file_path.display()));
+        }
+        if state.status != BackfillStatus::Paused && state.status != BackfillStatus::Failed {
+            return Err(anyhow!(
+                "cannot resume backfill in status {:?} (must be paused or failed)",
+                state.status
+            ));
+        }
+
+        let adapter = self.registry.get(agent_name).ok_or_else(|| anyhow!("adapter not found: {}", agent_name))?;
+        self.backfill_file(agent_name, file_path, adapter, batch_size).await
+    }
+
     async fn backfill_directory(&self, options: &BackfillOptions, adapter: Arc<dyn AgentAdapter>) -> Result<()> {
         info!("Scanning directory: {}", options.log_path.display());
         
@@ -137,7 +280,7 @@ impl BackfillManager {
         for entry in WalkDir::new(&options.log_path).into_iter().filter_map(|e| e.ok()) {
             if entry.file_type().is_file() {
                 if let Some(ext) = entry.path().extension() {
-                    if ext == "log" || ext == "txt" || ext == "json" || ext == "jsonl" || ext == "ndjson" {
+                    if ext == "log" || ext == "txt" || ext == "json" || ext == "jsonl" || ext == "ndjson" || ext == "vscdb" {
                         log_files.push(entry.path().to_path_buf());
                     }
                 }
@@ -169,20 +312,26 @@ impl BackfillManager {
 
         // Determine if we should use file-based or line-based parsing
         let use_file_parsing = self.should_use_file_parsing(adapter.as_ref(), file_path);
+        let token = CancellationToken::new();
 
         let result = if use_file_parsing {
-            self.backfill_file_whole(agent_name, file_path, adapter, &mut state, batch_size).await
+            self.backfill_file_whole(agent_name, file_path, adapter, &mut state, batch_size, &token).await
         } else {
-            self.backfill_file_line_by_line(agent_name, file_path, adapter, &mut state, batch_size).await
+            self.backfill_file_line_by_line(agent_name, file_path, adapter, &mut state, batch_size, &token).await
         };
 
         match result {
-            Ok(_) => {
+            Ok(true) => {
                 state.status = BackfillStatus::Completed;
                 state.completed_at = Some(Utc::now());
                 self.save_state(&mut state).await?;
                 info!("Completed backfill for {}", file_path.display());
             }
+            Ok(false) => {
+                state.status = BackfillStatus::Paused;
+                self.save_state(&mut state).await?;
+                info!("Paused backfill for {} at byte offset {}", file_path.display(), state.last_byte_offset);
+            }
             Err(e) => {
                 state.status = BackfillStatus::Failed;
                 state.error_message = Some(e.to_string());
@@ -195,6 +344,26 @@ impl BackfillManager {
         Ok(())
     }
 
+    /// Check whether `state`'s row has been flipped to [`BackfillStatus::Paused`] by a
+    /// `backfill pause` invocation (possibly from another process sharing this sqlite db), and if
+    /// so mark `token` cancelled so the caller's loop stops at its next checkpoint.
+    async fn poll_paused(&self, state: &BackfillState, token: &CancellationToken) -> Result<bool> {
+        if state.id == 0 {
+            return Ok(false);
+        }
+        let row = sqlx::query("SELECT status FROM backfill_state WHERE id = ?")
+            .bind(state.id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let paused = row
+            .map(|row| BackfillStatus::from(row.get::<String, _>(0)) == BackfillStatus::Paused)
+            .unwrap_or(false);
+        if paused {
+            token.cancel();
+        }
+        Ok(paused)
+    }
+
     fn should_use_file_parsing(&self, adapter: &dyn AgentAdapter, file_path: &Path) -> bool {
         let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
         let adapter_name = adapter.name();
@@ -203,31 +372,72 @@ impl BackfillManager {
             return true;
         }
 
+        // Cursor's state.vscdb is a SQLite file, not a line-oriented log: reader.lines() can't
+        // make sense of its binary pages, and CursorAdapter::parse_log_file already dispatches to
+        // its own sqlite reader internally, so whole-file parsing is the only path that works.
+        if adapter_name == "cursor" && ext == "vscdb" {
+            return true;
+        }
+
         false
     }
 
-    async fn backfill_file_whole(&self, _agent_name: &str, file_path: &Path, adapter: Arc<dyn AgentAdapter>, state: &mut BackfillState, batch_size: usize) -> Result<()> {
+    /// Returns `Ok(true)` if the file was fully processed, or `Ok(false)` if `token` was
+    /// cancelled (directly, or via an externally-set `Paused` status) and the run stopped early
+    /// at a checkpoint.
+    async fn backfill_file_whole(&self, _agent_name: &str, file_path: &Path, adapter: Arc<dyn AgentAdapter>, state: &mut BackfillState, batch_size: usize, token: &CancellationToken) -> Result<bool> {
         let events = adapter.parse_log_file(file_path).await?;
         info!("Parsed {} events from {}", events.len(), file_path.display());
 
         for chunk in events.chunks(batch_size) {
-            for event in chunk {
-                self.buffer.store(event).await?;
-            }
+            self.store_new_events(chunk).await?;
             state.total_events_processed += chunk.len() as i32;
             if let Some(last) = chunk.last() {
                 state.last_timestamp = Some(last.timestamp);
             }
+            // Poll before saving: save_state writes this run's status back, which would overwrite
+            // a `Paused` set by `pause` before it could be seen.
+            let paused = token.is_cancelled() || self.poll_paused(state, token).await?;
             self.save_state(state).await?;
+
+            if paused {
+                return Ok(false);
+            }
         }
 
-        Ok(())
+        // Whole-file parsing has no incremental byte offset to track as it goes, but once every
+        // event is stored the file has been consumed in full — record that so `backfill status`'s
+        // percent-complete column doesn't stay stuck at 0% for a row whose `STATUS` reads
+        // `completed`, matching how `backfill_file_line_by_line` sets `last_byte_offset` at EOF.
+        state.last_byte_offset = tokio::fs::metadata(file_path).await?.len() as i64;
+        self.save_state(state).await?;
+
+        Ok(true)
     }
 
-    async fn backfill_file_line_by_line(&self, _agent_name: &str, file_path: &Path, adapter: Arc<dyn AgentAdapter>, state: &mut BackfillState, batch_size: usize) -> Result<()> {
+    /// Returns `Ok(true)` if the file was read through to EOF, or `Ok(false)` if `token` was
+    /// cancelled (directly, or via an externally-set `Paused` status) and the run stopped early
+    /// at a checkpoint, leaving `state.last_byte_offset` at the last saved batch boundary.
+    async fn backfill_file_line_by_line(&self, _agent_name: &str, file_path: &Path, adapter: Arc<dyn AgentAdapter>, state: &mut BackfillState, batch_size: usize, token: &CancellationToken) -> Result<bool> {
         let file = File::open(file_path).await?;
         let file_size = file.metadata().await?.len() as i64;
-        
+
+        let fingerprint = fingerprint_file(file_path)?;
+        let rotated = match &state.fingerprint {
+            Some(previous) => *previous != fingerprint,
+            None => false,
+        };
+        if rotated || file_size < state.last_byte_offset {
+            if state.fingerprint.is_some() {
+                info!(
+                    "Detected log rotation/truncation for {}, restarting from byte 0",
+                    file_path.display()
+                );
+            }
+            state.last_byte_offset = 0;
+        }
+        state.fingerprint = Some(fingerprint);
+
         let mut reader = BufReader::new(file);
         if state.last_byte_offset > 0 {
             reader.seek(std::io::SeekFrom::Start(state.last_byte_offset as u64)).await?;
@@ -246,23 +456,24 @@ impl BackfillManager {
             }
 
             if batch.len() >= batch_size {
-                for event in &batch {
-                    self.buffer.store(event).await?;
-                }
+                self.store_new_events(&batch).await?;
                 state.total_events_processed += batch.len() as i32;
                 state.last_byte_offset = current_offset;
                 if let Some(last) = batch.last() {
                     state.last_timestamp = Some(last.timestamp);
                 }
+                let paused = token.is_cancelled() || self.poll_paused(state, token).await?;
                 self.save_state(state).await?;
                 batch.clear();
+
+                if paused {
+                    return Ok(false);
+                }
             }
         }
 
         if !batch.is_empty() {
-            for event in &batch {
-                self.buffer.store(event).await?;
-            }
+            self.store_new_events(&batch).await?;
             state.total_events_processed += batch.len() as i32;
             state.last_byte_offset = current_offset;
             if let Some(last) = batch.last() {
@@ -271,14 +482,18 @@ impl BackfillManager {
             self.save_state(state).await?;
         }
 
+        if token.is_cancelled() || self.poll_paused(state, token).await? {
+            return Ok(false);
+        }
+
         state.last_byte_offset = file_size;
-        Ok(())
+        Ok(true)
     }
 
     async fn load_state(&self, agent_name: &str, file_path: &Path) -> Result<BackfillState> {
         let path_str = file_path.to_string_lossy().to_string();
         let row = sqlx::query(
-            "SELECT id, agent_name, log_file_path, last_byte_offset, last_timestamp, total_events_processed, status, started_at, completed_at, error_message FROM backfill_state WHERE agent_name = ? AND log_file_path = ?"
+            "SELECT id, agent_name, log_file_path, last_byte_offset, fingerprint, last_timestamp, total_events_processed, status, started_at, completed_at, error_message FROM backfill_state WHERE agent_name = ? AND log_file_path = ?"
         )
         .bind(agent_name)
         .bind(&path_str)
@@ -291,12 +506,13 @@ impl BackfillManager {
                 agent_name: row.get(1),
                 log_file_path: row.get(2),
                 last_byte_offset: row.get(3),
-                last_timestamp: row.get::<Option<i64>, _>(4).map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
-                total_events_processed: row.get(5),
-                status: BackfillStatus::from(row.get::<String, _>(6)),
-                started_at: Utc.timestamp_opt(row.get(7), 0).unwrap(),
-                completed_at: row.get::<Option<i64>, _>(8).map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
-                error_message: row.get(9),
+                fingerprint: row.get(4),
+                last_timestamp: row.get::<Option<i64>, _>(5).map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+                total_events_processed: row.get(6),
+                status: BackfillStatus::from(row.get::<String, _>(7)),
+                started_at: Utc.timestamp_opt(row.get(8), 0).unwrap(),
+                completed_at: row.get::<Option<i64>, _>(9).map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+                error_message: row.get(10),
             })
         } else {
             Ok(BackfillState {
@@ -304,6 +520,7 @@ impl BackfillManager {
                 agent_name: agent_name.to_string(),
                 log_file_path: path_str,
                 last_byte_offset: 0,
+                fingerprint: None,
                 last_timestamp: None,
                 total_events_processed: 0,
                 status: BackfillStatus::New,
@@ -318,13 +535,14 @@ impl BackfillManager {
         if state.id == 0 {
             let res = sqlx::query(
                 r#"
-                INSERT INTO backfill_state (agent_name, log_file_path, last_byte_offset, last_timestamp, total_events_processed, status, started_at, completed_at, error_message)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                INSERT INTO backfill_state (agent_name, log_file_path, last_byte_offset, fingerprint, last_timestamp, total_events_processed, status, started_at, completed_at, error_message)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#
             )
             .bind(&state.agent_name)
             .bind(&state.log_file_path)
             .bind(state.last_byte_offset)
+            .bind(&state.fingerprint)
             .bind(state.last_timestamp.map(|ts| ts.timestamp()))
             .bind(state.total_events_processed)
             .bind(state.status.to_string())
@@ -339,11 +557,12 @@ impl BackfillManager {
             sqlx::query(
                 r#"
                 UPDATE backfill_state
-                SET last_byte_offset = ?, last_timestamp = ?, total_events_processed = ?, status = ?, completed_at = ?, error_message = ?
+                SET last_byte_offset = ?, fingerprint = ?, last_timestamp = ?, total_events_processed = ?, status = ?, completed_at = ?, error_message = ?
                 WHERE id = ?
                 "#
             )
             .bind(state.last_byte_offset)
+            .bind(&state.fingerprint)
             .bind(state.last_timestamp.map(|ts| ts.timestamp()))
             .bind(state.total_events_processed)
             .bind(state.status.to_string())
@@ -355,5 +574,205 @@ impl BackfillManager {
         }
         Ok(())
     }
+
+    /// Store `events` into the buffer, skipping any whose [`event_digest`] was already recorded
+    /// by a previous backfill run so re-running a backfill (or re-processing overlapping bytes
+    /// after a rotation reset) doesn't duplicate events downstream. Returns the number of events
+    /// actually stored.
+    async fn store_new_events(&self, events: &[AgentEvent]) -> Result<usize> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let digests: Vec<String> = events.iter().map(event_digest).collect();
+        let placeholders = digests.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        let query = format!("SELECT digest FROM event_digests WHERE digest IN ({placeholders})");
+        let mut q = sqlx::query(&query);
+        for digest in &digests {
+            q = q.bind(digest);
+        }
+        let rows = q.fetch_all(&self.pool).await?;
+        let known: HashSet<String> = rows.into_iter().map(|row| row.get(0)).collect();
+
+        let mut stored = 0;
+        for (event, digest) in events.iter().zip(digests.iter()) {
+            if known.contains(digest) {
+                continue;
+            }
+            self.buffer.store(event).await?;
+            sqlx::query("INSERT OR IGNORE INTO event_digests (digest, created_at) VALUES (?, ?)")
+                .bind(digest)
+                .bind(Utc::now().timestamp())
+                .execute(&self.pool)
+                .await?;
+            stored += 1;
+        }
+
+        Ok(stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use devlog_buffer::{Config as BufferConfig, SqliteStore};
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    /// Treats every non-empty line as one event, `id` set to the line's own content so tests can
+    /// assert on exactly which lines were (or weren't) parsed.
+    struct LineAdapter;
+
+    #[async_trait]
+    impl AgentAdapter for LineAdapter {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn parse_log_line(&self, line: &str) -> Result<Option<AgentEvent>> {
+            if line.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(AgentEvent {
+                id: line.to_string(),
+                timestamp: Utc::now(),
+                event_type: "test".to_string(),
+                agent_id: "test".to_string(),
+                agent_version: "".to_string(),
+                session_id: "".to_string(),
+                project_id: 0,
+                machine_id: None,
+                workspace_id: None,
+                legacy_project_id: None,
+                context: HashMap::new(),
+                data: HashMap::new(),
+                metrics: None,
+            }))
+        }
+
+        async fn parse_log_file(&self, file_path: &Path) -> Result<Vec<AgentEvent>> {
+            let content = tokio::fs::read_to_string(file_path).await?;
+            Ok(content.lines().filter_map(|line| self.parse_log_line(line).ok().flatten()).collect())
+        }
+
+        fn detection_confidence(&self, _sample: &str) -> f64 {
+            0.0
+        }
+    }
+
+    async fn new_manager() -> (BackfillManager, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let buffer = Arc::new(
+            SqliteStore::new(BufferConfig {
+                db_path: dir.path().join("buffer.db").to_string_lossy().to_string(),
+                max_size: 1000,
+            })
+            .await
+            .unwrap(),
+        );
+        let mut registry = Registry::new();
+        registry.register(Arc::new(LineAdapter));
+        let manager = BackfillManager::new(Config {
+            registry: Arc::new(registry),
+            buffer,
+            db_path: dir.path().join("backfill.db").to_string_lossy().to_string(),
+        })
+        .await
+        .unwrap();
+        (manager, dir)
+    }
+
+    fn sample_event(id: &str) -> AgentEvent {
+        AgentEvent {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            event_type: "test".to_string(),
+            agent_id: "test".to_string(),
+            agent_version: "".to_string(),
+            session_id: "".to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::new(),
+            data: HashMap::new(),
+            metrics: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_new_events_skips_already_recorded_digest() {
+        let (manager, _dir) = new_manager().await;
+        let event = sample_event("1");
+
+        let stored = manager.store_new_events(&[event.clone()]).await.unwrap();
+        assert_eq!(stored, 1);
+        assert_eq!(manager.buffer.count().await.unwrap(), 1);
+
+        let stored_again = manager.store_new_events(&[event]).await.unwrap();
+        assert_eq!(stored_again, 0);
+        assert_eq!(manager.buffer.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rotated_file_resets_last_byte_offset_to_zero() {
+        let (manager, dir) = new_manager().await;
+        let log_path = dir.path().join("agent.log");
+        tokio::fs::write(&log_path, "line-a\nline-b\nline-c\n").await.unwrap();
+
+        // Simulate a previous run that got further into a *different* file at this path: a
+        // fingerprint that won't match the current file, and an offset past the new file's end
+        // (so a seek-based resume, if it happened, would read nothing).
+        let mut state = manager.load_state("test", &log_path).await.unwrap();
+        state.fingerprint = Some("stale-fingerprint".to_string());
+        state.last_byte_offset = 10_000;
+        manager.save_state(&mut state).await.unwrap();
+
+        let token = CancellationToken::new();
+        let completed = manager
+            .backfill_file_line_by_line("test", &log_path, Arc::new(LineAdapter), &mut state, 10, &token)
+            .await
+            .unwrap();
+
+        assert!(completed);
+        assert_eq!(state.total_events_processed, 3);
+        assert_eq!(state.last_byte_offset, tokio::fs::metadata(&log_path).await.unwrap().len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_pause_stops_in_flight_run_at_next_batch_boundary() {
+        let (manager, dir) = new_manager().await;
+        let log_path = dir.path().join("agent.log");
+        tokio::fs::write(&log_path, "1\n2\n3\n4\n5\n6\n").await.unwrap();
+
+        let mut state = manager.load_state("test", &log_path).await.unwrap();
+        manager.save_state(&mut state).await.unwrap();
+        manager.pause("test", &log_path).await.unwrap();
+
+        let token = CancellationToken::new();
+        let completed = manager
+            .backfill_file_line_by_line("test", &log_path, Arc::new(LineAdapter), &mut state, 2, &token)
+            .await
+            .unwrap();
+
+        assert!(!completed);
+        // Stopped after the first batch boundary, not the whole 6-line file.
+        assert_eq!(state.total_events_processed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_resume_rejects_non_paused_or_failed_state() {
+        let (manager, dir) = new_manager().await;
+        let log_path = dir.path().join("agent.log");
+        tokio::fs::write(&log_path, "1\n").await.unwrap();
+
+        let mut state = manager.load_state("test", &log_path).await.unwrap();
+        state.status = BackfillStatus::Completed;
+        manager.save_state(&mut state).await.unwrap();
+
+        let result = manager.resume("test", &log_path, 10).await;
+        assert!(result.is_err());
+    }
 }
 
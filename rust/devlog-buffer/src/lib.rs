@@ -1,155 +1,108 @@
+use async_trait::async_trait;
 use devlog_core::AgentEvent;
-use sqlx::{sqlite::SqlitePool, Row};
-use anyhow::{Result, Context};
-use std::path::Path;
-use chrono::Utc;
-
-pub struct Buffer {
-    pool: SqlitePool,
-    max_size: usize,
-}
-
-pub struct Config {
-    pub db_path: String,
-    pub max_size: usize,
-}
-
-impl Buffer {
-    pub async fn new(config: Config) -> Result<Self> {
-        let db_url = format!("sqlite:{}", config.db_path);
-        
-        // Create file if it doesn't exist
-        if !Path::new(&config.db_path).exists() {
-            if let Some(parent) = Path::new(&config.db_path).parent() {
-                tokio::fs::create_dir_all(parent).await?;
-            }
-            tokio::fs::File::create(&config.db_path).await?;
-        }
-
-        let pool = SqlitePool::connect(&db_url).await.context("failed to connect to sqlite")?;
-
-        let buffer = Self {
-            pool,
-            max_size: if config.max_size == 0 { 10000 } else { config.max_size },
-        };
+use anyhow::Result;
+use chrono::{DateTime, Utc};
 
-        buffer.init_schema().await?;
+mod memory;
+mod sqlite;
 
-        Ok(buffer)
-    }
-
-    async fn init_schema(&self) -> Result<()> {
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS events (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                event_id TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                agent_id TEXT NOT NULL,
-                session_id TEXT NOT NULL,
-                project_id INTEGER NOT NULL,
-                data TEXT NOT NULL,
-                created_at INTEGER NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_timestamp ON events(timestamp);
-            CREATE INDEX IF NOT EXISTS idx_created_at ON events(created_at);
-            "#
-        )
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
-
-    pub async fn store(&self, event: &AgentEvent) -> Result<()> {
-        let count = self.count().await?;
+pub use memory::MemoryStore;
+pub use sqlite::{Config, SqliteStore};
 
-        if count >= self.max_size {
-            self.evict_oldest().await?;
-        }
+/// Column a [`QueryParams`] sort applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderField {
+    Timestamp,
+    CreatedAt,
+}
 
-        let data_json = serde_json::to_string(event)?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO events (event_id, timestamp, agent_id, session_id, project_id, data, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#
-        )
-        .bind(&event.id)
-        .bind(event.timestamp.timestamp())
-        .bind(&event.agent_id)
-        .bind(&event.session_id)
-        .bind(event.project_id)
-        .bind(data_json)
-        .bind(Utc::now().timestamp())
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+impl Default for OrderField {
+    fn default() -> Self {
+        OrderField::Timestamp
     }
+}
 
-    pub async fn retrieve(&self, limit: i32) -> Result<Vec<AgentEvent>> {
-        let rows = sqlx::query("SELECT data FROM events ORDER BY created_at ASC LIMIT ?")
-            .bind(limit)
-            .fetch_all(&self.pool)
-            .await?;
-
-        let mut events = Vec::new();
-        for row in rows {
-            let data_json: String = row.get(0);
-            let event: AgentEvent = serde_json::from_str(&data_json)?;
-            events.push(event);
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
 
-        Ok(events)
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder::Asc
     }
+}
 
-    pub async fn delete(&self, event_ids: &[String]) -> Result<()> {
-        if event_ids.is_empty() {
-            return Ok(());
-        }
-
-        // Build query with placeholders
-        let placeholders = vec!["?"; event_ids.len()].join(",");
-        let query_str = format!("DELETE FROM events WHERE event_id IN ({})", placeholders);
+/// Filter/sort/pagination parameters for [`EventStore::query`]. `start`/`end` bound `timestamp`
+/// (unix seconds); the rest are equality filters. All fields are optional except `limit`/`offset`,
+/// which default to returning the first page.
+#[derive(Debug, Clone)]
+pub struct QueryParams {
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub agent_id: Option<String>,
+    pub session_id: Option<String>,
+    pub project_id: Option<i32>,
+    pub event_type: Option<String>,
+    pub order_by: OrderField,
+    pub order: SortOrder,
+    pub limit: i64,
+    pub offset: i64,
+}
 
-        let mut query = sqlx::query(&query_str);
-        for id in event_ids {
-            query = query.bind(id);
+impl Default for QueryParams {
+    fn default() -> Self {
+        Self {
+            start: None,
+            end: None,
+            agent_id: None,
+            session_id: None,
+            project_id: None,
+            event_type: None,
+            order_by: OrderField::default(),
+            order: SortOrder::default(),
+            limit: 100,
+            offset: 0,
         }
+    }
+}
 
-        query.execute(&self.pool).await?;
-
-        Ok(())
+/// Storage backend for buffered events, abstracted so the server and the rest of the crate don't
+/// hard-wire themselves to SQLite. [`SqliteStore`] is the persistent, on-disk implementation used
+/// in production; [`MemoryStore`] trades persistence for zero disk I/O, which suits tests and
+/// ephemeral runs (CI, sandboxes) where there's nothing to resume after a restart anyway. Adding a
+/// new backend means implementing this trait, not touching the server routes.
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    async fn store(&self, event: &AgentEvent) -> Result<()>;
+
+    async fn retrieve(&self, limit: i32) -> Result<Vec<AgentEvent>>;
+
+    /// Events with `timestamp` in `[since, until)`, ordered oldest-first, up to `limit` rows.
+    /// Either bound may be omitted to leave that side open.
+    async fn retrieve_range(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i32) -> Result<Vec<AgentEvent>>;
+
+    /// Scan buffered events oldest-first and collect up to `limit` that satisfy `predicate`.
+    /// Defaults to filtering the unbounded [`Self::retrieve_range`] result, which every backend
+    /// already has to implement; override only if a backend can push the predicate down.
+    async fn retrieve_matching(&self, predicate: &(dyn Fn(&AgentEvent) -> bool + Send + Sync), limit: i32) -> Result<Vec<AgentEvent>> {
+        let candidates = self.retrieve_range(None, None, i32::MAX).await?;
+        Ok(candidates.into_iter().filter(|e| predicate(e)).take(limit.max(0) as usize).collect())
     }
 
-    pub async fn count(&self) -> Result<usize> {
-        let row = sqlx::query("SELECT COUNT(*) FROM events")
-            .fetch_one(&self.pool)
-            .await?;
+    /// Filtered/sorted/paginated read with a total-count for the same filter (ignoring
+    /// `limit`/`offset`), so callers can build pagination without a second round trip.
+    async fn query(&self, params: &QueryParams) -> Result<(Vec<AgentEvent>, i64)>;
 
-        let count: i64 = row.get(0);
-        Ok(count as usize)
-    }
+    async fn delete(&self, event_ids: &[String]) -> Result<()>;
 
-    async fn evict_oldest(&self) -> Result<()> {
-        sqlx::query(
-            "DELETE FROM events WHERE id = (SELECT id FROM events ORDER BY created_at ASC LIMIT 1)"
-        )
-        .execute(&self.pool)
-        .await?;
+    async fn count(&self) -> Result<usize>;
 
-        Ok(())
-    }
+    async fn clear(&self) -> Result<()>;
 
-    pub async fn clear(&self) -> Result<()> {
-        sqlx::query("DELETE FROM events").execute(&self.pool).await?;
-        Ok(())
-    }
+    async fn vacuum(&self) -> Result<()>;
 
-    pub async fn vacuum(&self) -> Result<()> {
-        sqlx::query("VACUUM").execute(&self.pool).await?;
-        Ok(())
-    }
+    /// Total events dropped to stay within the backend's size cap since construction.
+    fn evicted_total(&self) -> u64;
 }
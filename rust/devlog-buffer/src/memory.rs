@@ -0,0 +1,264 @@
+use crate::{EventStore, OrderField, QueryParams, SortOrder};
+use async_trait::async_trait;
+use devlog_core::AgentEvent;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// In-memory [`EventStore`] that never touches disk, trading persistence for the ability to run
+/// entirely in a process's own heap. Suits tests and ephemeral runs (CI, sandboxes) where there's
+/// nothing worth resuming after a restart anyway; production deployments should use
+/// [`SqliteStore`](crate::SqliteStore) instead.
+pub struct MemoryStore {
+    events: Mutex<VecDeque<AgentEvent>>,
+    max_size: usize,
+    evicted_total: AtomicU64,
+}
+
+impl MemoryStore {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::new()),
+            max_size: if max_size == 0 { 10000 } else { max_size },
+            evicted_total: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl EventStore for MemoryStore {
+    async fn store(&self, event: &AgentEvent) -> Result<()> {
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= self.max_size {
+            events.pop_front();
+            self.evicted_total.fetch_add(1, Ordering::Relaxed);
+        }
+        events.push_back(event.clone());
+        Ok(())
+    }
+
+    async fn retrieve(&self, limit: i32) -> Result<Vec<AgentEvent>> {
+        let events = self.events.lock().unwrap();
+        Ok(events.iter().take(limit.max(0) as usize).cloned().collect())
+    }
+
+    async fn retrieve_range(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i32) -> Result<Vec<AgentEvent>> {
+        let events = self.events.lock().unwrap();
+        Ok(events
+            .iter()
+            .filter(|e| since.map_or(true, |s| e.timestamp >= s) && until.map_or(true, |u| e.timestamp < u))
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn query(&self, params: &QueryParams) -> Result<(Vec<AgentEvent>, i64)> {
+        let events = self.events.lock().unwrap();
+        let mut matching: Vec<&AgentEvent> = events
+            .iter()
+            .filter(|e| {
+                params.start.map_or(true, |s| e.timestamp.timestamp() >= s)
+                    && params.end.map_or(true, |end| e.timestamp.timestamp() < end)
+                    && params.agent_id.as_deref().map_or(true, |v| v == e.agent_id)
+                    && params.session_id.as_deref().map_or(true, |v| v == e.session_id)
+                    && params.project_id.map_or(true, |v| v == e.project_id)
+                    && params.event_type.as_deref().map_or(true, |v| v == e.event_type)
+            })
+            .collect();
+
+        if params.order_by == OrderField::Timestamp {
+            matching.sort_by_key(|e| e.timestamp);
+        }
+        if params.order == SortOrder::Desc {
+            matching.reverse();
+        }
+
+        let total = matching.len() as i64;
+        let page = matching
+            .into_iter()
+            .skip(params.offset.max(0) as usize)
+            .take(params.limit.max(0) as usize)
+            .cloned()
+            .collect();
+
+        Ok((page, total))
+    }
+
+    async fn delete(&self, event_ids: &[String]) -> Result<()> {
+        self.events.lock().unwrap().retain(|e| !event_ids.contains(&e.id));
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<usize> {
+        Ok(self.events.lock().unwrap().len())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.events.lock().unwrap().clear();
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn evicted_total(&self) -> u64 {
+        self.evicted_total.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use devlog_core::EVENT_TYPE_LLM_REQUEST;
+    use std::collections::HashMap;
+
+    fn sample_event(id: &str, agent_id: &str, session_id: &str, project_id: i32, event_type: &str, timestamp: i64) -> AgentEvent {
+        AgentEvent {
+            id: id.to_string(),
+            timestamp: Utc.timestamp_opt(timestamp, 0).unwrap(),
+            event_type: event_type.to_string(),
+            agent_id: agent_id.to_string(),
+            agent_version: "1.0.0".to_string(),
+            session_id: session_id.to_string(),
+            project_id,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::new(),
+            data: HashMap::new(),
+            metrics: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_agent_id() {
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-b", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 101)).await.unwrap();
+
+        let params = QueryParams {
+            agent_id: Some("agent-a".to_string()),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "1");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_session_id() {
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-2", 1, EVENT_TYPE_LLM_REQUEST, 101)).await.unwrap();
+
+        let params = QueryParams {
+            session_id: Some("sess-2".to_string()),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_project_id_and_event_type() {
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 2, EVENT_TYPE_LLM_REQUEST, 101)).await.unwrap();
+        store.store(&sample_event("3", "agent-a", "sess-1", 1, "tool_use", 102)).await.unwrap();
+
+        let params = QueryParams {
+            project_id: Some(1),
+            event_type: Some(EVENT_TYPE_LLM_REQUEST.to_string()),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "1");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_start_and_end() {
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+        store.store(&sample_event("3", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 300)).await.unwrap();
+
+        let params = QueryParams {
+            start: Some(150),
+            end: Some(300),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_query_pagination() {
+        let store = MemoryStore::new(100);
+        for i in 0..5 {
+            store.store(&sample_event(&i.to_string(), "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100 + i)).await.unwrap();
+        }
+
+        let params = QueryParams {
+            limit: 2,
+            offset: 2,
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 5);
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_orders_by_timestamp_ascending_by_default() {
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 300)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("3", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+
+        let (events, _) = store.query(&QueryParams::default()).await.unwrap();
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "3", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_orders_by_timestamp_descending() {
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+
+        let params = QueryParams {
+            order: SortOrder::Desc,
+            ..Default::default()
+        };
+        let (events, _) = store.query(&params).await.unwrap();
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_orders_descending_by_created_at() {
+        // `MemoryStore` tracks no separate `created_at` column, so `OrderField::CreatedAt`
+        // falls back to insertion order, which is exactly creation order for an append-only
+        // `VecDeque` — unlike `timestamp`, which a caller-supplied event could set out of order.
+        let store = MemoryStore::new(100);
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+
+        let params = QueryParams {
+            order_by: OrderField::CreatedAt,
+            order: SortOrder::Desc,
+            ..Default::default()
+        };
+        let (events, _) = store.query(&params).await.unwrap();
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "1"]);
+    }
+}
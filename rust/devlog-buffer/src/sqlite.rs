@@ -0,0 +1,493 @@
+use crate::{EventStore, OrderField, QueryParams, SortOrder};
+use async_trait::async_trait;
+use devlog_core::AgentEvent;
+use sqlx::{sqlite::SqlitePool, Row};
+use anyhow::{Result, Context};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::{DateTime, Utc};
+
+/// Persistent, on-disk [`EventStore`] backed by SQLite. The default backend; see [`MemoryStore`](crate::MemoryStore)
+/// for the disk-free alternative.
+pub struct SqliteStore {
+    pool: SqlitePool,
+    max_size: usize,
+    /// Running total of events dropped by `evict_oldest`, surfaced at `GET /metrics` as
+    /// `devlog_events_evicted_total` so operators can tell `max_size` pressure from normal churn.
+    evicted_total: AtomicU64,
+}
+
+pub struct Config {
+    pub db_path: String,
+    pub max_size: usize,
+}
+
+impl SqliteStore {
+    pub async fn new(config: Config) -> Result<Self> {
+        let db_url = format!("sqlite:{}", config.db_path);
+
+        // Create file if it doesn't exist
+        if !Path::new(&config.db_path).exists() {
+            if let Some(parent) = Path::new(&config.db_path).parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::File::create(&config.db_path).await?;
+        }
+
+        let pool = SqlitePool::connect(&db_url).await.context("failed to connect to sqlite")?;
+
+        let store = Self {
+            pool,
+            max_size: if config.max_size == 0 { 10000 } else { config.max_size },
+            evicted_total: AtomicU64::new(0),
+        };
+
+        store.init_schema().await?;
+
+        Ok(store)
+    }
+
+    async fn init_schema(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_id TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                agent_id TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                project_id INTEGER NOT NULL,
+                event_type TEXT NOT NULL,
+                data TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_timestamp ON events(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_created_at ON events(created_at);
+            CREATE INDEX IF NOT EXISTS idx_agent_session ON events(agent_id, session_id);
+
+            CREATE TABLE IF NOT EXISTS watch_checkpoints (
+                path TEXT PRIMARY KEY,
+                checkpoint TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Load the last-persisted checkpoint for `path` (the watcher's serialized
+    /// `devlog_adapters::Checkpoint` JSON), or `None` if this source has never been checkpointed.
+    /// Kept storage-generic: the caller owns decoding the opaque string. Checkpointing is tied to
+    /// having a durable file to resume from, so it stays a `SqliteStore`-specific capability
+    /// rather than part of `EventStore`.
+    pub async fn load_checkpoint(&self, path: &str) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT checkpoint FROM watch_checkpoints WHERE path = ?")
+            .bind(path)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| r.get(0)))
+    }
+
+    /// Persist `checkpoint` for `path`, overwriting whatever was previously recorded.
+    pub async fn save_checkpoint(&self, path: &str, checkpoint: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO watch_checkpoints (path, checkpoint, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(path) DO UPDATE SET checkpoint = excluded.checkpoint, updated_at = excluded.updated_at
+            "#
+        )
+        .bind(path)
+        .bind(checkpoint)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Bind whichever of `params`'s filters are set, in the same order `query` appended their
+    /// placeholders in. Shared between the count and select statements so the two stay in sync.
+    fn bind_filters<'q>(
+        mut query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+        params: &'q QueryParams,
+    ) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+        if let Some(start) = params.start {
+            query = query.bind(start);
+        }
+        if let Some(end) = params.end {
+            query = query.bind(end);
+        }
+        if let Some(agent_id) = &params.agent_id {
+            query = query.bind(agent_id);
+        }
+        if let Some(session_id) = &params.session_id {
+            query = query.bind(session_id);
+        }
+        if let Some(project_id) = params.project_id {
+            query = query.bind(project_id);
+        }
+        if let Some(event_type) = &params.event_type {
+            query = query.bind(event_type);
+        }
+        query
+    }
+
+    async fn evict_oldest(&self) -> Result<()> {
+        sqlx::query(
+            "DELETE FROM events WHERE id = (SELECT id FROM events ORDER BY created_at ASC LIMIT 1)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.evicted_total.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventStore for SqliteStore {
+    async fn store(&self, event: &AgentEvent) -> Result<()> {
+        let count = self.count().await?;
+
+        if count >= self.max_size {
+            self.evict_oldest().await?;
+        }
+
+        let data_json = serde_json::to_string(event)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO events (event_id, timestamp, agent_id, session_id, project_id, event_type, data, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&event.id)
+        .bind(event.timestamp.timestamp())
+        .bind(&event.agent_id)
+        .bind(&event.session_id)
+        .bind(event.project_id)
+        .bind(&event.event_type)
+        .bind(data_json)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn retrieve(&self, limit: i32) -> Result<Vec<AgentEvent>> {
+        let rows = sqlx::query("SELECT data FROM events ORDER BY created_at ASC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let data_json: String = row.get(0);
+            let event: AgentEvent = serde_json::from_str(&data_json)?;
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    async fn retrieve_range(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>, limit: i32) -> Result<Vec<AgentEvent>> {
+        let rows = match (since, until) {
+            (Some(since), Some(until)) => {
+                sqlx::query("SELECT data FROM events WHERE timestamp >= ? AND timestamp < ? ORDER BY timestamp ASC LIMIT ?")
+                    .bind(since.timestamp())
+                    .bind(until.timestamp())
+                    .bind(limit)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            (Some(since), None) => {
+                sqlx::query("SELECT data FROM events WHERE timestamp >= ? ORDER BY timestamp ASC LIMIT ?")
+                    .bind(since.timestamp())
+                    .bind(limit)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            (None, Some(until)) => {
+                sqlx::query("SELECT data FROM events WHERE timestamp < ? ORDER BY timestamp ASC LIMIT ?")
+                    .bind(until.timestamp())
+                    .bind(limit)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            (None, None) => {
+                sqlx::query("SELECT data FROM events ORDER BY timestamp ASC LIMIT ?")
+                    .bind(limit)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+
+        let mut events = Vec::new();
+        for row in rows {
+            let data_json: String = row.get(0);
+            events.push(serde_json::from_str(&data_json)?);
+        }
+        Ok(events)
+    }
+
+    async fn query(&self, params: &QueryParams) -> Result<(Vec<AgentEvent>, i64)> {
+        let mut conditions = Vec::new();
+        if params.start.is_some() {
+            conditions.push("timestamp >= ?");
+        }
+        if params.end.is_some() {
+            conditions.push("timestamp < ?");
+        }
+        if params.agent_id.is_some() {
+            conditions.push("agent_id = ?");
+        }
+        if params.session_id.is_some() {
+            conditions.push("session_id = ?");
+        }
+        if params.project_id.is_some() {
+            conditions.push("project_id = ?");
+        }
+        if params.event_type.is_some() {
+            conditions.push("event_type = ?");
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let order_column = match params.order_by {
+            OrderField::Timestamp => "timestamp",
+            OrderField::CreatedAt => "created_at",
+        };
+        let order_direction = match params.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+
+        let count_sql = format!("SELECT COUNT(*) FROM events {}", where_clause);
+        let total: i64 = Self::bind_filters(sqlx::query(&count_sql), params)
+            .fetch_one(&self.pool)
+            .await?
+            .get(0);
+
+        let select_sql = format!(
+            "SELECT data FROM events {} ORDER BY {} {} LIMIT ? OFFSET ?",
+            where_clause, order_column, order_direction
+        );
+        let rows = Self::bind_filters(sqlx::query(&select_sql), params)
+            .bind(params.limit)
+            .bind(params.offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let data_json: String = row.get(0);
+            events.push(serde_json::from_str(&data_json)?);
+        }
+
+        Ok((events, total))
+    }
+
+    async fn delete(&self, event_ids: &[String]) -> Result<()> {
+        if event_ids.is_empty() {
+            return Ok(());
+        }
+
+        // Build query with placeholders
+        let placeholders = vec!["?"; event_ids.len()].join(",");
+        let query_str = format!("DELETE FROM events WHERE event_id IN ({})", placeholders);
+
+        let mut query = sqlx::query(&query_str);
+        for id in event_ids {
+            query = query.bind(id);
+        }
+
+        query.execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<usize> {
+        let row = sqlx::query("SELECT COUNT(*) FROM events")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let count: i64 = row.get(0);
+        Ok(count as usize)
+    }
+
+    async fn clear(&self) -> Result<()> {
+        sqlx::query("DELETE FROM events").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> Result<()> {
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    fn evicted_total(&self) -> u64 {
+        self.evicted_total.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use devlog_core::EVENT_TYPE_LLM_REQUEST;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    fn sample_event(id: &str, agent_id: &str, session_id: &str, project_id: i32, event_type: &str, timestamp: i64) -> AgentEvent {
+        AgentEvent {
+            id: id.to_string(),
+            timestamp: Utc.timestamp_opt(timestamp, 0).unwrap(),
+            event_type: event_type.to_string(),
+            agent_id: agent_id.to_string(),
+            agent_version: "1.0.0".to_string(),
+            session_id: session_id.to_string(),
+            project_id,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::new(),
+            data: HashMap::new(),
+            metrics: None,
+        }
+    }
+
+    async fn new_store() -> SqliteStore {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_buffer.db").to_string_lossy().to_string();
+        let store = SqliteStore::new(Config { db_path, max_size: 1000 }).await.unwrap();
+        // Keep the tempdir alive for the store's lifetime by leaking it; each test gets its own
+        // directory so this doesn't accumulate across the suite.
+        std::mem::forget(dir);
+        store
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_agent_id() {
+        let store = new_store().await;
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-b", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 101)).await.unwrap();
+
+        let params = QueryParams {
+            agent_id: Some("agent-a".to_string()),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, "1");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_session_id() {
+        let store = new_store().await;
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-2", 1, EVENT_TYPE_LLM_REQUEST, 101)).await.unwrap();
+
+        let params = QueryParams {
+            session_id: Some("sess-2".to_string()),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_project_id_and_event_type() {
+        let store = new_store().await;
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 2, EVENT_TYPE_LLM_REQUEST, 101)).await.unwrap();
+        store.store(&sample_event("3", "agent-a", "sess-1", 1, "tool_use", 102)).await.unwrap();
+
+        let params = QueryParams {
+            project_id: Some(1),
+            event_type: Some(EVENT_TYPE_LLM_REQUEST.to_string()),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "1");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_start_and_end() {
+        let store = new_store().await;
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+        store.store(&sample_event("3", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 300)).await.unwrap();
+
+        let params = QueryParams {
+            start: Some(150),
+            end: Some(300),
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_query_pagination() {
+        let store = new_store().await;
+        for i in 0..5 {
+            store.store(&sample_event(&i.to_string(), "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100 + i)).await.unwrap();
+        }
+
+        let params = QueryParams {
+            limit: 2,
+            offset: 2,
+            ..Default::default()
+        };
+        let (events, total) = store.query(&params).await.unwrap();
+        assert_eq!(total, 5);
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_orders_by_timestamp_ascending_by_default() {
+        let store = new_store().await;
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 300)).await.unwrap();
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        store.store(&sample_event("3", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+
+        let (events, _) = store.query(&QueryParams::default()).await.unwrap();
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "3", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_orders_descending_by_created_at() {
+        let store = new_store().await;
+        // `created_at` has one-second resolution, so the two inserts need a real gap between
+        // them to land in different seconds and make ordering deterministic.
+        store.store(&sample_event("1", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 100)).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        store.store(&sample_event("2", "agent-a", "sess-1", 1, EVENT_TYPE_LLM_REQUEST, 200)).await.unwrap();
+
+        let params = QueryParams {
+            order_by: OrderField::CreatedAt,
+            order: SortOrder::Desc,
+            ..Default::default()
+        };
+        let (events, _) = store.query(&params).await.unwrap();
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "1"]);
+    }
+}
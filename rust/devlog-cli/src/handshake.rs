@@ -0,0 +1,21 @@
+use anyhow::{Context, Result};
+use devlog_core::handshake::HandshakeResponse;
+
+/// Perform the version/capability handshake against `backend_url`'s `GET /api/version` before
+/// collection starts, so the collector learns what the backend can accept instead of finding out
+/// mid-stream via bulk-rejected batches.
+pub async fn negotiate(backend_url: &str) -> Result<HandshakeResponse> {
+    let url = format!("{}/api/version", backend_url.trim_end_matches('/'));
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("failed to reach backend at {} for version handshake", url))?;
+
+    response
+        .json::<HandshakeResponse>()
+        .await
+        .context("backend returned an invalid handshake response")
+}
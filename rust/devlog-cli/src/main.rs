@@ -1,16 +1,19 @@
 use clap::{Parser, Subcommand, CommandFactory};
 use clap_complete::{generate, Shell};
-use anyhow::Result;
-use devlog_core::{AgentEvent, config::Config};
+use anyhow::{anyhow, Context, Result};
+use devlog_core::{AgentEvent, config::Config, handshake::Capabilities};
 use devlog_adapters::{Registry, claude::ClaudeAdapter, copilot::CopilotAdapter, cursor::CursorAdapter};
-use devlog_buffer::{Buffer, Config as BufferConfig};
+use devlog_buffer::{EventStore, MemoryStore, SqliteStore, Config as BufferConfig};
 use devlog_watcher::{Watcher, Config as WatcherConfig};
 use devlog_backfill::{BackfillManager, Config as BackfillConfig, BackfillOptions};
+use devlog_docker::{Config as DockerPluginConfig, DockerPluginServer, DEFAULT_SOCKET_PATH};
+use devlog_telemetry::{Config as TelemetryConfig, Exporter as TelemetryExporter};
+use devlog_analytics::{Config as AnalyticsConfig, Sink as AnalyticsSink};
 use std::sync::Arc;
-use log::{info, error};
+use log::{info, warn, error, debug};
 use std::path::PathBuf;
 
-use devlog_cli::server;
+use devlog_cli::{handshake, server};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -21,6 +24,16 @@ struct Cli {
     #[arg(short, long, default_value = "~/.devlog/collector.json")]
     config: String,
 
+    /// Additional config file to layer on top of `--config`, in order (e.g. a local secrets
+    /// file). Repeat to layer more than one.
+    #[arg(long = "config-overlay")]
+    config_overlays: Vec<String>,
+
+    /// Explicit config override as `key=value` (dotted path, e.g. `collection.batchSize=50`),
+    /// applied after every file and environment layer. Repeatable.
+    #[arg(long = "set")]
+    overrides: Vec<String>,
+
     #[arg(short, long)]
     verbose: bool,
 }
@@ -52,6 +65,55 @@ enum Commands {
         #[arg(value_enum)]
         shell: Shell,
     },
+    /// Serve the Docker logging-driver plugin API over a Unix socket, so container
+    /// stdout/stderr streams into the buffer alongside file-based agent logs
+    DockerPlugin {
+        #[arg(long)]
+        socket_path: Option<PathBuf>,
+
+        /// Adapter used to parse reassembled container log lines
+        #[arg(long, default_value = "claude")]
+        adapter: String,
+    },
+    /// Parse an agent's log with its adapter and write the resulting events to a portable file,
+    /// so a session can be shipped between tools without re-parsing the original log
+    Export {
+        #[arg(short, long)]
+        agent: String,
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// File to write the exported events to
+        #[arg(short, long)]
+        output: PathBuf,
+
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: ExportFormatArg,
+    },
+    /// Load a previously exported event stream into the buffer
+    Import {
+        #[arg(short, long)]
+        input: PathBuf,
+
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: ExportFormatArg,
+    },
+}
+
+/// CLI-facing mirror of `devlog_core::EventFormat`, since the latter isn't `clap::ValueEnum`.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ExportFormatArg {
+    Jsonl,
+    Messagepack,
+}
+
+impl From<ExportFormatArg> for devlog_core::EventFormat {
+    fn from(value: ExportFormatArg) -> Self {
+        match value {
+            ExportFormatArg::Jsonl => devlog_core::EventFormat::Jsonl,
+            ExportFormatArg::Messagepack => devlog_core::EventFormat::MessagePack,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -68,6 +130,16 @@ enum BackfillCommands {
         #[arg(short, long)]
         agent: Option<String>,
     },
+    /// Pause a running or queued backfill, checkpointing at its next batch boundary
+    Pause {
+        agent: String,
+        path: PathBuf,
+    },
+    /// Resume a paused or failed backfill from its saved byte offset
+    Resume {
+        agent: String,
+        path: PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -82,13 +154,61 @@ async fn main() -> Result<()> {
     env_logger::init();
 
     // Load config
-    let config = Config::load(&cli.config)?;
+    let overrides = cli
+        .overrides
+        .iter()
+        .map(|entry| {
+            entry
+                .split_once('=')
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .ok_or_else(|| anyhow!("invalid --set value {:?}, expected key=value", entry))
+        })
+        .collect::<Result<std::collections::HashMap<_, _>>>()?;
+    let mut config = Config::load(&cli.config, &cli.config_overlays, &overrides)?;
 
     match cli.command {
         Commands::Start { no_history, initial_sync_days, port } => {
             info!("Starting Devlog Collector...");
             info!("Initial sync days: {}", initial_sync_days);
-            
+
+            // Reserve the port before spawning anything, so a conflict fails fast here instead
+            // of the server task silently logging an error while the daemon appears to run.
+            let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("failed to bind collector server to {}", addr))?;
+
+            // Negotiate protocol version and capabilities with the backend before collecting
+            // anything, so we adapt to what it can accept instead of finding out from bulk
+            // rejections later. A handshake failure (backend unreachable, old backend with no
+            // /api/version route) degrades to unrestricted capabilities rather than blocking
+            // startup — only an actual major-version mismatch is fatal.
+            let capabilities = match handshake::negotiate(&config.backend_url).await {
+                Ok(response) => {
+                    if !devlog_core::handshake::PROTOCOL_VERSION.is_compatible_with(&response.protocol_version) {
+                        return Err(anyhow!(
+                            "backend protocol v{} is incompatible with collector protocol v{}",
+                            response.protocol_version,
+                            devlog_core::handshake::PROTOCOL_VERSION,
+                        ));
+                    }
+                    if response.protocol_version.minor != devlog_core::handshake::PROTOCOL_VERSION.minor {
+                        warn!(
+                            "backend protocol v{} differs in minor version from collector v{}; some capabilities may be degraded",
+                            response.protocol_version,
+                            devlog_core::handshake::PROTOCOL_VERSION,
+                        );
+                    }
+                    response.capabilities
+                }
+                Err(e) => {
+                    warn!("version handshake with backend failed, proceeding with unrestricted capabilities: {}", e);
+                    Capabilities::default()
+                }
+            };
+            config.collection.batch_size = config.collection.batch_size.min(capabilities.max_batch_size).max(1);
+            let capabilities = Arc::new(capabilities);
+
             // Initialize components
             let mut registry = Registry::new();
             registry.register(Arc::new(ClaudeAdapter::new(config.project_id.clone())));
@@ -96,7 +216,9 @@ async fn main() -> Result<()> {
             registry.register(Arc::new(CursorAdapter::new(config.project_id.clone())));
             let registry = Arc::new(registry);
 
-            let buffer = Arc::new(Buffer::new(BufferConfig {
+            // The watcher always persists its checkpoints to SQLite (resuming after a restart
+            // only makes sense with a durable file), regardless of which backend serves events.
+            let sqlite_store = Arc::new(SqliteStore::new(BufferConfig {
                 db_path: config.buffer.db_path.clone(),
                 max_size: config.buffer.max_size,
             }).await?);
@@ -105,26 +227,66 @@ async fn main() -> Result<()> {
                 registry: registry.clone(),
                 event_queue_size: 1000,
                 debounce_ms: 100,
+                buffer: sqlite_store.clone(),
             })?;
 
-            // Start processing events from watcher
+            let buffer: Arc<dyn EventStore> = match config.buffer.backend.as_str() {
+                "memory" => Arc::new(MemoryStore::new(config.buffer.max_size)),
+                _ => sqlite_store,
+            };
+
+            let telemetry = match &config.telemetry.otlp_endpoint {
+                Some(endpoint) => {
+                    let exporter = TelemetryExporter::new(TelemetryConfig {
+                        otlp_endpoint: endpoint.clone(),
+                        service_name: "devlog-collector".to_string(),
+                    }).context("failed to initialize OTLP exporter")?;
+                    info!("Exporting events to OTLP collector at {}", endpoint);
+                    Some(Arc::new(exporter))
+                }
+                None => None,
+            };
+
+            let analytics = match &config.analytics.dsn {
+                Some(dsn) => {
+                    let sink = AnalyticsSink::new(AnalyticsConfig {
+                        dsn: dsn.clone(),
+                        database: config.analytics.database.clone(),
+                        table: config.analytics.table.clone(),
+                        batch_max_events: config.analytics.batch_max_events,
+                        batch_max_interval_ms: config.analytics.batch_max_interval_ms,
+                        max_retries: config.analytics.max_retries,
+                    }).context("failed to initialize ClickHouse analytics sink")?;
+                    info!("Streaming events to ClickHouse analytics sink at {}", dsn);
+                    Some(Arc::new(sink))
+                }
+                None => None,
+            };
+
+            let server_state = Arc::new(server::AppState::new(buffer.clone(), capabilities.clone(), config.auth.tokens.clone(), telemetry, analytics));
+
+            // Start processing events from watcher: store into the buffer and publish to the
+            // live broadcast bus so `/events/stream` and `/query` subscribers see them too.
             let buffer_clone = buffer.clone();
+            let events_tx = server_state.events_tx.clone();
             tokio::spawn(async move {
-                while let Some(event) = rx.recv().await {
+                while let Some(mut event) = rx.recv().await {
+                    if !capabilities.apply(&mut event) {
+                        debug!("Dropping event of unsupported type {} per negotiated capabilities", event.event_type);
+                        continue;
+                    }
                     info!("Received event from watcher: {} - {}", event.event_type, event.id);
                     if let Err(e) = buffer_clone.store(&event).await {
                         error!("Failed to store event from watcher: {}", e);
+                        continue;
                     }
+                    let _ = events_tx.send(event);
                 }
             });
 
-            // Start HTTP server
-            let server_state = Arc::new(server::AppState {
-                buffer: buffer.clone(),
-            });
-            
+            // Start HTTP server on the listener we already reserved above.
             tokio::spawn(async move {
-                if let Err(e) = server::start_server(server_state, port).await {
+                if let Err(e) = server::start_server(server_state, listener).await {
                     error!("Server error: {}", e);
                 }
             });
@@ -152,7 +314,7 @@ async fn main() -> Result<()> {
             registry.register(Arc::new(CursorAdapter::new(config.project_id.clone())));
             let registry = Arc::new(registry);
 
-            let buffer = Arc::new(Buffer::new(BufferConfig {
+            let buffer = Arc::new(SqliteStore::new(BufferConfig {
                 db_path: config.buffer.db_path.clone(),
                 max_size: config.buffer.max_size,
             }).await?);
@@ -173,8 +335,42 @@ async fn main() -> Result<()> {
                     }).await?;
                 }
                 BackfillCommands::Status { agent } => {
-                    info!("Checking backfill status for {:?}", agent);
-                    // TODO: implement backfill status
+                    let rows = manager.status(agent.as_deref()).await?;
+                    if rows.is_empty() {
+                        println!("No backfill state recorded yet.");
+                    } else {
+                        println!(
+                            "{:<12} {:<40} {:<11} {:>10} {:>8} {:<26} {}",
+                            "AGENT", "PATH", "STATUS", "EVENTS", "PROGRESS", "LAST TIMESTAMP", "ERROR"
+                        );
+                        for row in rows {
+                            let file_size = std::fs::metadata(&row.log_file_path).map(|m| m.len() as i64).unwrap_or(0);
+                            let progress = if file_size > 0 {
+                                format!("{:.1}%", (row.last_byte_offset as f64 / file_size as f64 * 100.0).min(100.0))
+                            } else {
+                                "-".to_string()
+                            };
+                            let last_timestamp = row.last_timestamp.map(|ts| ts.to_string()).unwrap_or_else(|| "-".to_string());
+                            println!(
+                                "{:<12} {:<40} {:<11} {:>10} {:>8} {:<26} {}",
+                                row.agent_name,
+                                row.log_file_path,
+                                row.status.to_string(),
+                                row.total_events_processed,
+                                progress,
+                                last_timestamp,
+                                row.error_message.as_deref().unwrap_or("-"),
+                            );
+                        }
+                    }
+                }
+                BackfillCommands::Pause { agent, path } => {
+                    manager.pause(&agent, &path).await?;
+                    info!("Paused backfill for {} at {}", agent, path.display());
+                }
+                BackfillCommands::Resume { agent, path } => {
+                    info!("Resuming backfill for {} at {}", agent, path.display());
+                    manager.resume(&agent, &path, 100).await?;
                 }
             }
         }
@@ -183,6 +379,55 @@ async fn main() -> Result<()> {
             let name = cmd.get_name().to_string();
             generate(shell, &mut cmd, name, &mut std::io::stdout());
         }
+        Commands::DockerPlugin { socket_path, adapter } => {
+            let mut registry = Registry::new();
+            registry.register(Arc::new(ClaudeAdapter::new(config.project_id.clone())));
+            registry.register(Arc::new(CopilotAdapter::new(config.project_id.clone())));
+            registry.register(Arc::new(CursorAdapter::new(config.project_id.clone())));
+            let registry = Arc::new(registry);
+
+            let buffer = Arc::new(SqliteStore::new(BufferConfig {
+                db_path: config.buffer.db_path.clone(),
+                max_size: config.buffer.max_size,
+            }).await?);
+
+            let socket_path = socket_path.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
+            info!("Starting Docker logging-driver plugin on {}", socket_path.display());
+
+            let server = DockerPluginServer::new(DockerPluginConfig {
+                registry,
+                buffer,
+                socket_path,
+                adapter_name: adapter,
+            });
+
+            server.serve().await?;
+        }
+        Commands::Export { agent, path, output, format } => {
+            let mut registry = Registry::new();
+            registry.register(Arc::new(ClaudeAdapter::new(config.project_id.clone())));
+            registry.register(Arc::new(CopilotAdapter::new(config.project_id.clone())));
+            registry.register(Arc::new(CursorAdapter::new(config.project_id.clone())));
+
+            let adapter = registry.get(&agent).ok_or_else(|| anyhow!("no registered adapter named {:?}", agent))?;
+            let events = adapter.parse_log_file(&path).await?;
+            let count = events.len();
+            devlog_core::write_events(&output, format.into(), events)?;
+            info!("Exported {} events from {} to {}", count, path.display(), output.display());
+        }
+        Commands::Import { input, format } => {
+            let buffer = Arc::new(SqliteStore::new(BufferConfig {
+                db_path: config.buffer.db_path.clone(),
+                max_size: config.buffer.max_size,
+            }).await?);
+
+            let events = devlog_core::read_events(&input, format.into())?;
+            let count = events.len();
+            for event in events {
+                buffer.store(&event).await?;
+            }
+            info!("Imported {} events from {} into the buffer", count, input.display());
+        }
     }
 
     Ok(())
@@ -2,32 +2,171 @@ use axum::{
     routing::{get, post},
     Router,
     Json,
-    extract::{State, WebSocketUpgrade, ws::{WebSocket, Message}},
-    response::IntoResponse,
+    body::{Body, Bytes},
+    extract::{Path, Query, Request, State, WebSocketUpgrade, ws::{WebSocket, Message}},
+    http::{header::{self, AUTHORIZATION}, StatusCode},
+    middleware::{self, Next},
+    response::{sse::{Event as SseEvent, KeepAlive, Sse}, IntoResponse, Response},
 };
-use std::sync::Arc;
-use devlog_core::AgentEvent;
-use devlog_buffer::Buffer;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use devlog_core::{AgentEvent, handshake::Capabilities, query::{BatchIterator, StreamMode, StreamParameters}};
+use devlog_buffer::EventStore;
+use serde::Deserialize;
 use serde_json::json;
-use log::{info, error};
+use log::{info, error, debug};
 use tower_http::cors::CorsLayer;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::{errors::BroadcastStreamRecvError, BroadcastStream}, Stream, StreamExt};
+
+/// Backlog of live events kept for subscribers that briefly fall behind a `/query` or `/ws`
+/// stream before they're considered lagged and skip ahead.
+const EVENT_BROADCAST_CAPACITY: usize = 1024;
 
 pub struct AppState {
-    pub buffer: Arc<Buffer>,
+    pub buffer: Arc<dyn EventStore>,
+    pub events_tx: broadcast::Sender<AgentEvent>,
+    /// Negotiated with the backend at startup (see `handshake::negotiate`); defaults to
+    /// unrestricted when no handshake has happened. Applied to every event ingested here, not
+    /// just the ones the file watcher hands off, so `/events` and `/ws` can't push event types
+    /// or fields the backend never advertised support for.
+    capabilities: Arc<Capabilities>,
+    /// Accepted `Authorization: Bearer <token>` values. Empty means auth is disabled.
+    auth_tokens: Vec<String>,
+    /// Mirrors every stored event into OTel spans/counters when configured. `None` when
+    /// `telemetry.otlpEndpoint` isn't set, so the server behaves exactly as before.
+    telemetry: Option<Arc<devlog_telemetry::Exporter>>,
+    /// Streams every stored event to ClickHouse in the background when configured. `None` when
+    /// `analytics.dsn` isn't set, so the server behaves exactly as before.
+    analytics: Option<Arc<devlog_analytics::Sink>>,
+    /// Counters/gauges surfaced in Prometheus text format at `GET /metrics`.
+    metrics: ServerMetrics,
+}
+
+#[derive(Default)]
+struct ServerMetrics {
+    events_ingested_total: AtomicU64,
+    agent_totals: Mutex<HashMap<String, AgentTotals>>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct AgentTotals {
+    tokens: u64,
+    cost: f64,
 }
 
-pub async fn start_server(state: Arc<AppState>, port: u16) -> anyhow::Result<()> {
+impl AppState {
+    pub fn new(
+        buffer: Arc<dyn EventStore>,
+        capabilities: Arc<Capabilities>,
+        auth_tokens: Vec<String>,
+        telemetry: Option<Arc<devlog_telemetry::Exporter>>,
+        analytics: Option<Arc<devlog_analytics::Sink>>,
+    ) -> Self {
+        let (events_tx, _) = broadcast::channel(EVENT_BROADCAST_CAPACITY);
+        Self { buffer, events_tx, capabilities, auth_tokens, telemetry, analytics, metrics: ServerMetrics::default() }
+    }
+
+    /// Record one successfully-stored event against the ingestion counter and, if it carries
+    /// token/cost metrics, against its agent's running totals.
+    fn record_ingested(&self, event: &AgentEvent) {
+        self.metrics.events_ingested_total.fetch_add(1, Ordering::Relaxed);
+
+        let Some(event_metrics) = &event.metrics else { return };
+        let tokens = (event_metrics.prompt_tokens.unwrap_or(0) + event_metrics.response_tokens.unwrap_or(0)).max(0) as u64;
+        let cost = event_metrics.cost.unwrap_or(0.0);
+        if tokens == 0 && cost == 0.0 {
+            return;
+        }
+
+        let mut totals = self.metrics.agent_totals.lock().unwrap();
+        let entry = totals.entry(event.agent_id.clone()).or_default();
+        entry.tokens += tokens;
+        entry.cost += cost;
+    }
+}
+
+/// Require a valid `Authorization: Bearer <token>` header on every route it's layered onto,
+/// matched against `state.auth_tokens` with a constant-time compare. Skipped entirely when
+/// `auth_tokens` is empty, so deployments that haven't configured `auth.tokens` keep working
+/// unauthenticated.
+async fn require_auth(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, StatusCode> {
+    if state.auth_tokens.is_empty() {
+        return Ok(next.run(req).await);
+    }
+
+    let provided = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let authorized = match provided {
+        Some(token) => state.auth_tokens.iter().any(|expected| constant_time_eq(expected.as_bytes(), token.as_bytes())),
+        None => false,
+    };
+
+    if authorized {
+        Ok(next.run(req).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Compares `a` and `b` in time that depends only on `max(a.len(), b.len())`, not on where (or
+/// whether) they first differ. A length-mismatch early return would otherwise leak, via timing,
+/// whether a guessed token's length matches any configured one.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = (a.len() != b.len()) as u8;
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod auth_tests {
+    use super::constant_time_eq;
+
+    #[test]
+    fn test_constant_time_eq_matches_equal_slices() {
+        assert!(constant_time_eq(b"secret-token", b"secret-token"));
+    }
+
+    #[test]
+    fn test_constant_time_eq_rejects_different_content_same_length() {
+        assert!(!constant_time_eq(b"secret-token", b"secret-tokeX"));
+    }
+
+    #[test]
+    fn test_constant_time_eq_rejects_different_length() {
+        assert!(!constant_time_eq(b"short", b"much-longer-token"));
+        assert!(!constant_time_eq(b"much-longer-token", b"short"));
+    }
+}
+
+pub async fn start_server(state: Arc<AppState>, listener: TcpListener) -> anyhow::Result<()> {
+    let authenticated = Router::new()
+        .route("/events", post(ingest_events).get(get_events))
+        .route("/query", post(query_events))
+        .route("/events/stream", get(events_stream))
+        .route("/sessions/:session_id", get(get_session_summary))
+        .route("/ws", get(ws_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth));
+
     let app = Router::new()
         .route("/health", get(health_check))
-        .route("/events", post(ingest_events))
-        .route("/ws", get(ws_handler))
+        .route("/metrics", get(metrics_handler))
+        .merge(authenticated)
         .with_state(state)
         .layer(CorsLayer::permissive());
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
-    info!("Server listening on {}", addr);
-    
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Server listening on {}", listener.local_addr()?);
     axum::serve(listener, app).await?;
 
     Ok(())
@@ -37,18 +176,276 @@ async fn health_check() -> impl IntoResponse {
     Json(json!({ "status": "ok" }))
 }
 
+/// `GET /metrics`: buffer/ingestion statistics in Prometheus text exposition format, so an
+/// operator can scrape throughput, eviction pressure, and cost-per-agent without querying the
+/// SQLite buffer directly. Left outside the authenticated route group, alongside `/health`, since
+/// scrape access is typically controlled at the network layer rather than via bearer tokens.
+async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let buffer_size = state.buffer.count().await.unwrap_or(0);
+    let evicted = state.buffer.evicted_total();
+    let ingested = state.metrics.events_ingested_total.load(Ordering::Relaxed);
+
+    let mut body = String::new();
+    body.push_str("# HELP devlog_events_ingested_total Total events accepted via /events and /ws.\n");
+    body.push_str("# TYPE devlog_events_ingested_total counter\n");
+    body.push_str(&format!("devlog_events_ingested_total {}\n", ingested));
+
+    body.push_str("# HELP devlog_events_evicted_total Total events evicted from the buffer once max_size was reached.\n");
+    body.push_str("# TYPE devlog_events_evicted_total counter\n");
+    body.push_str(&format!("devlog_events_evicted_total {}\n", evicted));
+
+    body.push_str("# HELP devlog_buffer_size Current number of events held in the buffer.\n");
+    body.push_str("# TYPE devlog_buffer_size gauge\n");
+    body.push_str(&format!("devlog_buffer_size {}\n", buffer_size));
+
+    body.push_str("# HELP devlog_agent_tokens_total Total prompt+response tokens recorded per agent.\n");
+    body.push_str("# TYPE devlog_agent_tokens_total counter\n");
+    body.push_str("# HELP devlog_agent_cost_total Total cost recorded per agent.\n");
+    body.push_str("# TYPE devlog_agent_cost_total counter\n");
+    for (agent_id, totals) in state.metrics.agent_totals.lock().unwrap().iter() {
+        let agent_id = escape_prometheus_label(agent_id);
+        body.push_str(&format!("devlog_agent_tokens_total{{agent_id=\"{}\"}} {}\n", agent_id, totals.tokens));
+        body.push_str(&format!("devlog_agent_cost_total{{agent_id=\"{}\"}} {}\n", agent_id, totals.cost));
+    }
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Escape a string for use inside a Prometheus exposition-format label value, so a
+/// client-supplied `agent_id` containing `"`, `\`, or a newline can't break the scraped text
+/// format or bleed into the following metric line.
+fn escape_prometheus_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
 async fn ingest_events(
     State(state): State<Arc<AppState>>,
     Json(events): Json<Vec<AgentEvent>>,
 ) -> impl IntoResponse {
-    for event in events {
+    for mut event in events {
+        if !state.capabilities.apply(&mut event) {
+            debug!("Dropping event of unsupported type {} per negotiated capabilities", event.event_type);
+            continue;
+        }
         if let Err(e) = state.buffer.store(&event).await {
             error!("Failed to store event: {}", e);
+            continue;
         }
+        state.record_ingested(&event);
+        if let Some(exporter) = &state.telemetry {
+            exporter.record(&event);
+        }
+        if let Some(sink) = &state.analytics {
+            sink.enqueue(&event);
+        }
+        // Ignore the "no active subscribers" error; `/query` and `/ws` listeners are optional.
+        let _ = state.events_tx.send(event);
     }
     Json(json!({ "status": "success" }))
 }
 
+/// Query string accepted by `GET /events`, mapping 1:1 onto `devlog_buffer::QueryParams`.
+/// `start`/`end` are unix seconds; `order_by`/`order` fall back to `timestamp`/`asc` for any
+/// unrecognized value rather than rejecting the request.
+#[derive(Debug, Deserialize, Default)]
+struct EventsQuery {
+    start: Option<i64>,
+    end: Option<i64>,
+    agent_id: Option<String>,
+    session_id: Option<String>,
+    project_id: Option<i32>,
+    event_type: Option<String>,
+    order_by: Option<String>,
+    order: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+impl From<EventsQuery> for devlog_buffer::QueryParams {
+    fn from(query: EventsQuery) -> Self {
+        let mut params = devlog_buffer::QueryParams {
+            start: query.start,
+            end: query.end,
+            agent_id: query.agent_id,
+            session_id: query.session_id,
+            project_id: query.project_id,
+            event_type: query.event_type,
+            ..Default::default()
+        };
+
+        if let Some("created_at") = query.order_by.as_deref() {
+            params.order_by = devlog_buffer::OrderField::CreatedAt;
+        }
+        if let Some("desc") = query.order.as_deref() {
+            params.order = devlog_buffer::SortOrder::Desc;
+        }
+        if let Some(limit) = query.limit {
+            params.limit = limit;
+        }
+        if let Some(offset) = query.offset {
+            params.offset = offset;
+        }
+
+        params
+    }
+}
+
+/// `GET /events`: filtered/sorted/paginated read over the buffer, with the total matching count
+/// (ignoring `limit`/`offset`) in the `X-Total-Count` header so clients can page without a
+/// separate count request.
+async fn get_events(State(state): State<Arc<AppState>>, Query(query): Query<EventsQuery>) -> impl IntoResponse {
+    let params: devlog_buffer::QueryParams = query.into();
+
+    match state.buffer.query(&params).await {
+        Ok((events, total)) => {
+            ([("x-total-count", total.to_string())], Json(events)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to query events: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response()
+        }
+    }
+}
+
+/// `GET /sessions/{session_id}`: fold every buffered event for the session into a
+/// `devlog_core::SessionSummary` (lifecycle state, token/cost totals, tool/file activity, errors),
+/// so callers get a session's health at a glance instead of re-deriving it from raw events
+/// client-side. 404s if the buffer has no events for the session.
+async fn get_session_summary(State(state): State<Arc<AppState>>, Path(session_id): Path<String>) -> impl IntoResponse {
+    let params = devlog_buffer::QueryParams {
+        session_id: Some(session_id.clone()),
+        limit: i64::MAX,
+        ..Default::default()
+    };
+    let events = match state.buffer.query(&params).await {
+        Ok((events, _total)) => events,
+        Err(e) => {
+            error!("Failed to query session events: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+
+    if events.is_empty() {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "session not found" }))).into_response();
+    }
+
+    Json(devlog_core::summarize_session(&session_id, &events)).into_response()
+}
+
+/// Handle `POST /query`: stream events matching `params.selectors` as newline-delimited JSON
+/// arrays ("batches"), each flushed once its serialized size reaches `chunk_size_target`.
+///
+/// - `Snapshot` drains everything currently buffered, then ends with one final (possibly
+///   empty) flush as the end-of-stream marker.
+/// - `Subscribe` skips the buffer and only emits events arriving after connect.
+/// - `SnapshotThenSubscribe` does both, with no gap between the two phases because the
+///   subscription is opened before the snapshot is read.
+async fn query_events(State(state): State<Arc<AppState>>, Json(params): Json<StreamParameters>) -> impl IntoResponse {
+    let mut live_rx = state.events_tx.subscribe();
+    let buffer = state.buffer.clone();
+    let selectors = params.selectors;
+    let stream_mode = params.stream_mode;
+    let chunk_size_target = params.chunk_size_target;
+
+    let body_stream = async_stream::stream! {
+        let mut batch = BatchIterator::new(chunk_size_target);
+        // Ids already sent in the snapshot phase: the broadcast subscription is opened before the
+        // snapshot is read (so nothing is missed), but that means an event stored just before the
+        // snapshot query runs can show up in both the snapshot *and* the live subscription. Track
+        // what the snapshot already emitted so `SnapshotThenSubscribe` doesn't double-deliver it.
+        let mut snapshotted_ids = std::collections::HashSet::new();
+
+        if matches!(stream_mode, StreamMode::Snapshot | StreamMode::SnapshotThenSubscribe) {
+            match buffer.retrieve_matching(&|event| selectors.matches(event), i32::MAX).await {
+                Ok(events) => {
+                    for event in events {
+                        snapshotted_ids.insert(event.id.clone());
+                        if let Some(chunk) = batch.push(event) {
+                            yield encode_batch(&chunk);
+                        }
+                    }
+                    yield encode_batch(&batch.flush());
+                }
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+
+        if matches!(stream_mode, StreamMode::Subscribe | StreamMode::SnapshotThenSubscribe) {
+            loop {
+                match live_rx.recv().await {
+                    Ok(event) => {
+                        if snapshotted_ids.remove(&event.id) {
+                            continue;
+                        }
+                        if selectors.matches(&event) {
+                            if let Some(chunk) = batch.push(event) {
+                                yield encode_batch(&chunk);
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("/query subscriber lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    };
+
+    Body::from_stream(body_stream)
+}
+
+fn encode_batch(batch: &[AgentEvent]) -> anyhow::Result<Bytes> {
+    let mut line = serde_json::to_vec(batch)?;
+    line.push(b'\n');
+    Ok(Bytes::from(line))
+}
+
+/// Per-subscriber filters for `GET /events/stream`, each an exact match against the named
+/// field; omitted filters pass everything through.
+#[derive(Debug, Deserialize, Default)]
+struct EventStreamFilter {
+    agent: Option<String>,
+    event_type: Option<String>,
+    session: Option<String>,
+}
+
+impl EventStreamFilter {
+    fn matches(&self, event: &AgentEvent) -> bool {
+        self.agent.as_deref().map_or(true, |agent| agent == event.agent_id)
+            && self.event_type.as_deref().map_or(true, |event_type| event_type == event.event_type)
+            && self.session.as_deref().map_or(true, |session| session == event.session_id)
+    }
+}
+
+/// Handle `GET /events/stream`: tail the live event bus as Server-Sent Events, optionally
+/// narrowed by the `agent`/`event_type`/`session` query-string filters. A subscriber that falls
+/// behind the broadcast channel's buffer is told via a `lagged` comment event and skips ahead,
+/// rather than stalling the publishers that feed the channel.
+async fn events_stream(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<EventStreamFilter>,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let rx = state.events_tx.subscribe();
+
+    let stream = BroadcastStream::new(rx).filter_map(move |item| match item {
+        Ok(event) if filter.matches(&event) => Some(
+            SseEvent::default()
+                .json_data(&event)
+                .unwrap_or_else(|_| SseEvent::default().comment("failed to serialize event")),
+        ),
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            Some(SseEvent::default().comment(format!("lagged {} events", skipped)))
+        }
+    });
+
+    Sse::new(stream.map(Ok::<SseEvent, Infallible>)).keep_alive(KeepAlive::default())
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
@@ -56,20 +453,167 @@ async fn ws_handler(
     ws.on_upgrade(|socket| handle_socket(socket, state))
 }
 
+/// Subscription filter sent as the first text frame on `/ws`, mirroring `EventStreamFilter`'s
+/// match semantics (plus `project_id`, since a WS client is more likely to need per-project
+/// scoping than a browser tab already pointed at one backend). An unparseable or non-text first
+/// frame is treated as "subscribe to everything" rather than closing the connection, so existing
+/// clients that immediately start sending events (the pre-subscription ingest behavior) keep
+/// working.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct WsSubscriptionFilter {
+    agent_id: Option<String>,
+    session_id: Option<String>,
+    project_id: Option<i32>,
+    event_type: Option<String>,
+}
+
+impl WsSubscriptionFilter {
+    fn matches(&self, event: &AgentEvent) -> bool {
+        self.agent_id.as_deref().map_or(true, |v| v == event.agent_id)
+            && self.session_id.as_deref().map_or(true, |v| v == event.session_id)
+            && self.project_id.map_or(true, |v| v == event.project_id)
+            && self.event_type.as_deref().map_or(true, |v| v == event.event_type)
+    }
+}
+
+#[cfg(test)]
+mod ws_filter_tests {
+    use super::{AgentEvent, WsSubscriptionFilter};
+    use std::collections::HashMap;
+
+    fn sample_event() -> AgentEvent {
+        AgentEvent {
+            id: "evt-1".to_string(),
+            timestamp: chrono::Utc::now(),
+            event_type: "tool_use".to_string(),
+            agent_id: "claude".to_string(),
+            agent_version: "1.0".to_string(),
+            session_id: "sess-1".to_string(),
+            project_id: 7,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::new(),
+            data: HashMap::new(),
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_default_filter_matches_everything() {
+        assert!(WsSubscriptionFilter::default().matches(&sample_event()));
+    }
+
+    #[test]
+    fn test_filter_matches_only_on_every_set_field_at_once() {
+        let filter = WsSubscriptionFilter {
+            agent_id: Some("claude".to_string()),
+            session_id: Some("sess-1".to_string()),
+            project_id: Some(7),
+            event_type: Some("tool_use".to_string()),
+        };
+        assert!(filter.matches(&sample_event()));
+    }
+
+    #[test]
+    fn test_filter_rejects_event_when_any_single_field_mismatches() {
+        let mut filter = WsSubscriptionFilter { agent_id: Some("cursor".to_string()), ..Default::default() };
+        assert!(!filter.matches(&sample_event()));
+
+        filter = WsSubscriptionFilter { session_id: Some("sess-2".to_string()), ..Default::default() };
+        assert!(!filter.matches(&sample_event()));
+
+        filter = WsSubscriptionFilter { project_id: Some(9), ..Default::default() };
+        assert!(!filter.matches(&sample_event()));
+
+        filter = WsSubscriptionFilter { event_type: Some("llm_request".to_string()), ..Default::default() };
+        assert!(!filter.matches(&sample_event()));
+    }
+}
+
+/// Store `event` (from an ingest-style `/ws` frame) and mirror it to telemetry/analytics/the
+/// live broadcast bus, exactly like `POST /events` ingestion.
+async fn ingest_ws_event(state: &Arc<AppState>, mut event: AgentEvent) {
+    if !state.capabilities.apply(&mut event) {
+        debug!("Dropping event of unsupported type {} per negotiated capabilities", event.event_type);
+        return;
+    }
+    if let Err(e) = state.buffer.store(&event).await {
+        error!("Failed to store event from WS: {}", e);
+        return;
+    }
+    state.record_ingested(&event);
+    if let Some(exporter) = &state.telemetry {
+        exporter.record(&event);
+    }
+    if let Some(sink) = &state.analytics {
+        sink.enqueue(&event);
+    }
+    let _ = state.events_tx.send(event);
+}
+
+/// Bidirectional `/ws` loop. The first text frame is tried as an `AgentEvent` before anything
+/// else: every field of `WsSubscriptionFilter` is optional, so parsing it first would silently
+/// accept a real event as an (empty) filter and drop it. Only a frame that *isn't* a valid
+/// `AgentEvent` is treated as a [`WsSubscriptionFilter`]. Every subsequent frame is handled two
+/// ways at once — text frames shaped like an `AgentEvent` are still stored and re-broadcast
+/// exactly as before, while matching events from the broadcast bus (including other clients' and
+/// this connection's own ingested events) are pushed out as JSON text frames. A lagged receiver
+/// gets a gap-notice frame instead of silently missing events or having its connection dropped.
 async fn handle_socket(mut socket: WebSocket, state: Arc<AppState>) {
-    while let Some(msg) = socket.recv().await {
-        let msg = match msg {
-            Ok(msg) => msg,
-            Err(e) => {
-                error!("WebSocket error: {}", e);
-                return;
+    let filter = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => match serde_json::from_str::<AgentEvent>(&text) {
+            Ok(event) => {
+                ingest_ws_event(&state, event).await;
+                WsSubscriptionFilter::default()
             }
-        };
+            Err(_) => serde_json::from_str::<WsSubscriptionFilter>(&text).unwrap_or_default(),
+        },
+        Some(Ok(_)) => WsSubscriptionFilter::default(),
+        Some(Err(e)) => {
+            error!("WebSocket error: {}", e);
+            return;
+        }
+        None => return,
+    };
 
-        if let Message::Text(text) = msg {
-            if let Ok(event) = serde_json::from_str::<AgentEvent>(&text) {
-                if let Err(e) = state.buffer.store(&event).await {
-                    error!("Failed to store event from WS: {}", e);
+    let mut live_rx = state.events_tx.subscribe();
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let Some(msg) = incoming else { break };
+                let msg = match msg {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        error!("WebSocket error: {}", e);
+                        break;
+                    }
+                };
+
+                if let Message::Text(text) = msg {
+                    if let Ok(event) = serde_json::from_str::<AgentEvent>(&text) {
+                        ingest_ws_event(&state, event).await;
+                    }
+                }
+            }
+            live = live_rx.recv() => {
+                match live {
+                    Ok(event) if filter.matches(&event) => {
+                        let Ok(payload) = serde_json::to_string(&event) else { continue };
+                        if socket.send(Message::Text(payload)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        let gap = json!({ "lagged": skipped }).to_string();
+                        if socket.send(Message::Text(gap)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
         }
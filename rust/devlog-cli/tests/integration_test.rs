@@ -1,5 +1,5 @@
 use devlog_cli::server;
-use devlog_buffer::{Buffer, Config as BufferConfig};
+use devlog_buffer::{EventStore, SqliteStore, Config as BufferConfig};
 use devlog_core::AgentEvent;
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
@@ -12,19 +12,19 @@ async fn test_server_health_and_ingest() {
     let dir = tempdir().unwrap();
     let db_path = dir.path().join("test_buffer.db").to_string_lossy().to_string();
 
-    let buffer = Arc::new(Buffer::new(BufferConfig {
+    let buffer = Arc::new(SqliteStore::new(BufferConfig {
         db_path: db_path.clone(),
         max_size: 100,
     }).await.unwrap());
 
-    let state = Arc::new(server::AppState {
-        buffer: buffer.clone(),
-    });
+    let state = Arc::new(server::AppState::new(buffer.clone(), Arc::new(Default::default()), Vec::new(), None, None));
 
     let port = 3201; // Use a different port for testing
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     let state_clone = state.clone();
     tokio::spawn(async move {
-        server::start_server(state_clone, port).await.unwrap();
+        server::start_server(state_clone, listener).await.unwrap();
     });
 
     // Wait for server to start
@@ -67,7 +67,214 @@ async fn test_server_health_and_ingest() {
 
     // Verify event is in buffer
     // Wait a bit for async storage if any (though it's awaited in ingest_events)
-    let stored_events = buffer.get_unsynced(10).await.unwrap();
+    let stored_events = buffer.retrieve(10).await.unwrap();
     assert_eq!(stored_events.len(), 1);
     assert_eq!(stored_events[0].id, "test-id");
 }
+
+#[tokio::test]
+async fn test_events_stream_filters_by_agent() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test_buffer.db").to_string_lossy().to_string();
+
+    let buffer = Arc::new(SqliteStore::new(BufferConfig {
+        db_path,
+        max_size: 100,
+    }).await.unwrap());
+
+    let state = Arc::new(server::AppState::new(buffer.clone(), Arc::new(Default::default()), Vec::new(), None, None));
+
+    let port = 3202; // Use a different port for testing
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        server::start_server(state_clone, listener).await.unwrap();
+    });
+
+    sleep(Duration::from_millis(500)).await;
+
+    let client = reqwest::Client::new();
+    let mut stream_res = client
+        .get(format!("http://localhost:{}/events/stream?agent=claude", port))
+        .send()
+        .await
+        .unwrap()
+        .bytes_stream();
+
+    // Give the SSE subscriber time to connect before anything is published.
+    sleep(Duration::from_millis(200)).await;
+
+    let make_event = |agent_id: &str| AgentEvent {
+        id: format!("{}-id", agent_id),
+        timestamp: Utc::now(),
+        event_type: "test-event".to_string(),
+        agent_id: agent_id.to_string(),
+        agent_version: "1.0".to_string(),
+        session_id: "test-session".to_string(),
+        project_id: 1,
+        machine_id: None,
+        workspace_id: None,
+        legacy_project_id: None,
+        context: std::collections::HashMap::new(),
+        data: std::collections::HashMap::new(),
+        metrics: None,
+    };
+
+    client.post(format!("http://localhost:{}/events", port))
+        .json(&vec![make_event("cursor")])
+        .send()
+        .await
+        .unwrap();
+    client.post(format!("http://localhost:{}/events", port))
+        .json(&vec![make_event("claude")])
+        .send()
+        .await
+        .unwrap();
+
+    // Read SSE frames until the matching event shows up or we time out waiting for it.
+    use futures_util::StreamExt;
+    let mut received = String::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match tokio::time::timeout(remaining, stream_res.next()).await {
+            Ok(Some(Ok(bytes))) => {
+                received.push_str(&String::from_utf8_lossy(&bytes));
+                if received.contains("claude-id") {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    assert!(received.contains("claude-id"), "expected claude event in SSE stream: {received}");
+    assert!(!received.contains("cursor-id"), "cursor event should have been filtered out: {received}");
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_reports_ingestion_and_agent_totals() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test_buffer.db").to_string_lossy().to_string();
+
+    let buffer = Arc::new(SqliteStore::new(BufferConfig {
+        db_path,
+        max_size: 100,
+    }).await.unwrap());
+
+    let state = Arc::new(server::AppState::new(buffer.clone(), Arc::new(Default::default()), Vec::new(), None, None));
+
+    let port = 3203; // Use a different port for testing
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        server::start_server(state_clone, listener).await.unwrap();
+    });
+
+    sleep(Duration::from_millis(500)).await;
+
+    let client = reqwest::Client::new();
+
+    let event = AgentEvent {
+        id: "metrics-id".to_string(),
+        timestamp: Utc::now(),
+        event_type: "test-event".to_string(),
+        agent_id: "claude".to_string(),
+        agent_version: "1.0".to_string(),
+        session_id: "test-session".to_string(),
+        project_id: 1,
+        machine_id: None,
+        workspace_id: None,
+        legacy_project_id: None,
+        context: std::collections::HashMap::new(),
+        data: std::collections::HashMap::new(),
+        metrics: Some(devlog_core::EventMetrics {
+            prompt_tokens: Some(10),
+            response_tokens: Some(5),
+            cost: Some(0.02),
+            ..Default::default()
+        }),
+    };
+
+    client.post(format!("http://localhost:{}/events", port))
+        .json(&vec![event])
+        .send()
+        .await
+        .unwrap();
+
+    let body = client.get(format!("http://localhost:{}/metrics", port))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert!(body.contains("devlog_events_ingested_total 1"), "unexpected body: {body}");
+    assert!(body.contains("devlog_buffer_size 1"), "unexpected body: {body}");
+    assert!(body.contains(r#"devlog_agent_tokens_total{agent_id="claude"} 15"#), "unexpected body: {body}");
+    assert!(body.contains(r#"devlog_agent_cost_total{agent_id="claude"} 0.02"#), "unexpected body: {body}");
+}
+
+#[tokio::test]
+async fn test_get_events_filters_by_agent_and_reports_total_count_header() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test_buffer.db").to_string_lossy().to_string();
+
+    let buffer = Arc::new(SqliteStore::new(BufferConfig {
+        db_path,
+        max_size: 100,
+    }).await.unwrap());
+
+    let state = Arc::new(server::AppState::new(buffer.clone(), Arc::new(Default::default()), Vec::new(), None, None));
+
+    let port = 3204; // Use a different port for testing
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        server::start_server(state_clone, listener).await.unwrap();
+    });
+
+    sleep(Duration::from_millis(500)).await;
+
+    let client = reqwest::Client::new();
+
+    let make_event = |agent_id: &str, id: &str| AgentEvent {
+        id: id.to_string(),
+        timestamp: Utc::now(),
+        event_type: "test-event".to_string(),
+        agent_id: agent_id.to_string(),
+        agent_version: "1.0".to_string(),
+        session_id: "test-session".to_string(),
+        project_id: 1,
+        machine_id: None,
+        workspace_id: None,
+        legacy_project_id: None,
+        context: std::collections::HashMap::new(),
+        data: std::collections::HashMap::new(),
+        metrics: None,
+    };
+
+    client.post(format!("http://localhost:{}/events", port))
+        .json(&vec![make_event("cursor", "cursor-1"), make_event("claude", "claude-1"), make_event("claude", "claude-2")])
+        .send()
+        .await
+        .unwrap();
+
+    let res = client
+        .get(format!("http://localhost:{}/events?agent_id=claude&limit=1", port))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.headers().get("x-total-count").unwrap().to_str().unwrap(), "2");
+    let events: Vec<AgentEvent> = res.json().await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].agent_id, "claude");
+}
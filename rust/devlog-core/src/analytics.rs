@@ -0,0 +1,178 @@
+//! One-pass aggregation of a stream of `AgentEvent`s into frequency/token/cost rollups,
+//! driven incrementally so the caller never has to hold the whole stream in memory.
+use crate::{AgentEvent, EVENT_TYPE_TOOL_USE};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Prompt/response/total token totals, summed for one model or one session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenTotals {
+    pub prompt_tokens: i64,
+    pub response_tokens: i64,
+    pub total_tokens: i64,
+}
+
+impl TokenTotals {
+    fn add(&mut self, prompt_tokens: i64, response_tokens: i64) {
+        self.prompt_tokens += prompt_tokens;
+        self.response_tokens += response_tokens;
+        self.total_tokens += prompt_tokens + response_tokens;
+    }
+}
+
+/// The finalized, serializable result of an aggregation pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsReport {
+    pub event_counts: HashMap<String, u64>,
+    pub model_token_totals: HashMap<String, TokenTotals>,
+    pub session_token_totals: HashMap<String, TokenTotals>,
+    pub total_cost: f64,
+    pub min_timestamp: Option<DateTime<Utc>>,
+    pub max_timestamp: Option<DateTime<Utc>>,
+    pub top_tools: Vec<(String, u64)>,
+}
+
+/// Incrementally folds `AgentEvent`s into frequency/token/cost rollups; call `update` from
+/// an adapter's parse loop, then `finalize` once to get the report.
+#[derive(Debug, Default)]
+pub struct MetricsAggregator {
+    event_counts: HashMap<String, u64>,
+    model_token_totals: HashMap<String, TokenTotals>,
+    session_token_totals: HashMap<String, TokenTotals>,
+    total_cost: f64,
+    min_timestamp: Option<DateTime<Utc>>,
+    max_timestamp: Option<DateTime<Utc>>,
+    tool_counts: HashMap<String, u64>,
+}
+
+impl MetricsAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, event: &AgentEvent) {
+        *self.event_counts.entry(event.event_type.clone()).or_insert(0) += 1;
+
+        self.min_timestamp = Some(self.min_timestamp.map_or(event.timestamp, |t| t.min(event.timestamp)));
+        self.max_timestamp = Some(self.max_timestamp.map_or(event.timestamp, |t| t.max(event.timestamp)));
+
+        if let Some(metrics) = &event.metrics {
+            let prompt_tokens = metrics.prompt_tokens.unwrap_or(0) as i64;
+            let response_tokens = metrics.response_tokens.unwrap_or(0) as i64;
+
+            if prompt_tokens != 0 || response_tokens != 0 {
+                if !event.session_id.is_empty() {
+                    self.session_token_totals
+                        .entry(event.session_id.clone())
+                        .or_default()
+                        .add(prompt_tokens, response_tokens);
+                }
+
+                if let Some(model) = event.context.get("model").and_then(|v| v.as_str()) {
+                    self.model_token_totals
+                        .entry(model.to_string())
+                        .or_default()
+                        .add(prompt_tokens, response_tokens);
+                }
+            }
+
+            if let Some(cost) = metrics.cost {
+                self.total_cost += cost;
+            }
+        }
+
+        if event.event_type == EVENT_TYPE_TOOL_USE {
+            if let Some(name) = event.data.get("toolName").and_then(|v| v.as_str()) {
+                *self.tool_counts.entry(name.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Consume the accumulated state into a serializable report, keeping only the `top_n`
+    /// most frequently used tools.
+    pub fn finalize(self, top_n: usize) -> AnalyticsReport {
+        let mut top_tools: Vec<(String, u64)> = self.tool_counts.into_iter().collect();
+        top_tools.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_tools.truncate(top_n);
+
+        AnalyticsReport {
+            event_counts: self.event_counts,
+            model_token_totals: self.model_token_totals,
+            session_token_totals: self.session_token_totals,
+            total_cost: self.total_cost,
+            min_timestamp: self.min_timestamp,
+            max_timestamp: self.max_timestamp,
+            top_tools,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventMetrics, EVENT_TYPE_LLM_REQUEST};
+    use serde_json::json;
+    use std::collections::HashMap as Map;
+
+    fn make_event(event_type: &str, session_id: &str, model: Option<&str>, metrics: Option<EventMetrics>) -> AgentEvent {
+        let mut context = Map::new();
+        if let Some(model) = model {
+            context.insert("model".to_string(), json!(model));
+        }
+        AgentEvent {
+            id: "e1".to_string(),
+            timestamp: Utc::now(),
+            event_type: event_type.to_string(),
+            agent_id: "claude".to_string(),
+            agent_version: "1.0".to_string(),
+            session_id: session_id.to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context,
+            data: Map::new(),
+            metrics,
+        }
+    }
+
+    #[test]
+    fn test_aggregates_counts_tokens_and_cost() {
+        let mut agg = MetricsAggregator::new();
+        agg.update(&make_event(
+            EVENT_TYPE_LLM_REQUEST,
+            "s1",
+            Some("claude-3-5-sonnet"),
+            Some(EventMetrics { prompt_tokens: Some(10), cost: Some(0.01), ..Default::default() }),
+        ));
+        agg.update(&make_event(
+            EVENT_TYPE_TOOL_USE,
+            "s1",
+            None,
+            None,
+        ));
+
+        let report = agg.finalize(5);
+        assert_eq!(report.event_counts[EVENT_TYPE_LLM_REQUEST], 1);
+        assert_eq!(report.model_token_totals["claude-3-5-sonnet"].prompt_tokens, 10);
+        assert_eq!(report.session_token_totals["s1"].prompt_tokens, 10);
+        assert!((report.total_cost - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_top_n_tools_truncated_and_sorted() {
+        let mut agg = MetricsAggregator::new();
+        for _ in 0..3 {
+            let mut e = make_event(EVENT_TYPE_TOOL_USE, "s1", None, None);
+            e.data.insert("toolName".to_string(), json!("search"));
+            agg.update(&e);
+        }
+        let mut e = make_event(EVENT_TYPE_TOOL_USE, "s1", None, None);
+        e.data.insert("toolName".to_string(), json!("edit"));
+        agg.update(&e);
+
+        let report = agg.finalize(1);
+        assert_eq!(report.top_tools, vec![("search".to_string(), 3)]);
+    }
+}
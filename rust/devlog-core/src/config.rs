@@ -2,8 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context, anyhow};
-use regex::Regex;
-use std::env;
+
+/// Env var prefix for the environment config layer (e.g. `DEVLOG_BACKEND_URL`). Nested fields use
+/// a doubled separator, e.g. `DEVLOG_COLLECTION__BATCH_SIZE` for `collection.batchSize`.
+const ENV_PREFIX: &str = "DEVLOG";
+const ENV_NESTED_SEPARATOR: &str = "__";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +20,12 @@ pub struct Config {
     pub backfill: BackfillConfig,
     pub agents: HashMap<String, AgentConfig>,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +43,14 @@ pub struct BufferConfig {
     pub enabled: bool,
     pub max_size: usize,
     pub db_path: String,
+    /// Storage backend for buffered events: `"sqlite"` (default, persistent) or `"memory"` (no
+    /// disk I/O, for tests and ephemeral runs — see `devlog_buffer::MemoryStore`).
+    #[serde(default = "default_backend")]
+    pub backend: String,
+}
+
+fn default_backend() -> String {
+    "sqlite".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +73,79 @@ pub struct LoggingConfig {
     pub file: String,
 }
 
+/// Bearer tokens accepted by the collector's HTTP server for authenticated routes. An empty
+/// `tokens` list (the default) leaves those routes open, matching today's no-auth behavior for
+/// existing deployments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub tokens: Vec<String>,
+}
+
+/// OTLP export is entirely optional: leaving `otlp_endpoint` unset (the default) means no
+/// exporter is built and events only land in the `Buffer`, matching today's behavior.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+}
+
+/// ClickHouse analytics export is entirely optional: leaving `dsn` unset (the default) means no
+/// background sink is spawned and events only land in the configured `EventStore`, matching
+/// today's behavior. The batch thresholds bound how long an event can sit unexported and how big
+/// a single insert gets, independent of `buffer.maxSize`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsConfig {
+    #[serde(default)]
+    pub dsn: Option<String>,
+    #[serde(default = "default_analytics_database")]
+    pub database: String,
+    #[serde(default = "default_analytics_table")]
+    pub table: String,
+    #[serde(default = "default_analytics_batch_max_events")]
+    pub batch_max_events: usize,
+    #[serde(default = "default_analytics_batch_max_interval_ms")]
+    pub batch_max_interval_ms: u64,
+    #[serde(default = "default_analytics_max_retries")]
+    pub max_retries: u32,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            dsn: None,
+            database: default_analytics_database(),
+            table: default_analytics_table(),
+            batch_max_events: default_analytics_batch_max_events(),
+            batch_max_interval_ms: default_analytics_batch_max_interval_ms(),
+            max_retries: default_analytics_max_retries(),
+        }
+    }
+}
+
+fn default_analytics_database() -> String {
+    "devlog".to_string()
+}
+
+fn default_analytics_table() -> String {
+    "agent_events".to_string()
+}
+
+fn default_analytics_batch_max_events() -> usize {
+    500
+}
+
+fn default_analytics_batch_max_interval_ms() -> u64 {
+    5000
+}
+
+fn default_analytics_max_retries() -> u32 {
+    5
+}
+
 impl Default for Config {
     fn default() -> Self {
         let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -81,6 +171,7 @@ impl Default for Config {
                 enabled: true,
                 max_size: 10000,
                 db_path: devlog_dir.join("buffer.db").to_string_lossy().to_string(),
+                backend: default_backend(),
             },
             backfill: BackfillConfig {
                 db_path: devlog_dir.join("backfill.db").to_string_lossy().to_string(),
@@ -90,52 +181,58 @@ impl Default for Config {
                 level: "info".to_string(),
                 file: devlog_dir.join("collector.log").to_string_lossy().to_string(),
             },
+            auth: AuthConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            analytics: AnalyticsConfig::default(),
         }
     }
 }
 
 impl Config {
-    pub fn load(path: &str) -> Result<Self> {
-        let path = expand_path(path);
-        
-        let mut s = config::Config::builder();
-        
-        // Start with defaults
+    /// Build the final config by layering sources in increasing precedence: the built-in
+    /// [`Default`], `path` (the primary config file), `extra_sources` in order (e.g. a committed
+    /// base config followed by a local secrets file), a `DEVLOG_`-prefixed environment layer, and
+    /// finally `overrides` (dotted-key values supplied directly by the CLI, e.g. `--set
+    /// collection.batchSize=50`). Missing files in `path`/`extra_sources` are skipped rather than
+    /// erroring, so a fresh install with no config file yet still loads the defaults.
+    pub fn load(path: &str, extra_sources: &[String], overrides: &HashMap<String, String>) -> Result<Self> {
         let default_config = Self::default();
-        
-        if Path::new(&path).exists() {
-            s = s.add_source(config::File::with_name(&path));
-        }
-        
-        let mut config: Config = s.build()?.try_deserialize()?;
-        
-        // Expand environment variables
-        config.expand_env_vars()?;
-        
-        // Validate
+        let mut builder = config::Config::builder()
+            .add_source(config::Config::try_from(&default_config).context("failed to load built-in defaults")?);
+
+        for source_path in std::iter::once(path).chain(extra_sources.iter().map(String::as_str)) {
+            let source_path = expand_path(source_path);
+            if Path::new(&source_path).exists() {
+                builder = builder.add_source(config::File::with_name(&source_path));
+            }
+        }
+
+        builder = builder.add_source(
+            config::Environment::with_prefix(ENV_PREFIX)
+                .prefix_separator("_")
+                .separator(ENV_NESTED_SEPARATOR)
+                .try_parsing(true),
+        );
+
+        for (key, value) in overrides {
+            builder = builder.set_override(key.as_str(), value.as_str())?;
+        }
+
+        let mut config: Config = builder.build()?.try_deserialize()?;
+
+        config.expand_paths();
         config.validate()?;
-        
+
         Ok(config)
     }
 
-    fn expand_env_vars(&mut self) -> Result<()> {
-        let re = Regex::new(r"\$\{([^}]+)\}")?;
-        
-        let expand = |s: &str| -> String {
-            re.replace_all(s, |caps: &regex::Captures| {
-                let var_name = &caps[1];
-                env::var(var_name).unwrap_or_else(|_| caps[0].to_string())
-            }).to_string()
-        };
-
-        self.backend_url = expand(&self.backend_url);
-        self.api_key = expand(&self.api_key);
-        self.project_id = expand(&self.project_id);
-        self.buffer.db_path = expand_path(&expand(&self.buffer.db_path));
-        self.backfill.db_path = expand_path(&expand(&self.backfill.db_path));
-        self.logging.file = expand_path(&expand(&self.logging.file));
-
-        Ok(())
+    /// Tilde-expand the on-disk paths we actually open at runtime. Other fields (backend URL, API
+    /// key, project id) no longer need ad-hoc `${VAR}` substitution now that the environment layer
+    /// in `load` lets operators override them directly.
+    fn expand_paths(&mut self) {
+        self.buffer.db_path = expand_path(&self.buffer.db_path);
+        self.backfill.db_path = expand_path(&self.backfill.db_path);
+        self.logging.file = expand_path(&self.logging.file);
     }
 
     pub fn validate(&self) -> Result<()> {
@@ -160,26 +257,36 @@ impl Config {
         if self.buffer.max_size < 100 || self.buffer.max_size > 100000 {
             return Err(anyhow!("buffer.maxSize must be between 100 and 100000"));
         }
-        
+        if !["sqlite", "memory"].contains(&self.buffer.backend.as_str()) {
+            return Err(anyhow!("buffer.backend must be one of: sqlite, memory"));
+        }
+
         let valid_log_levels = ["debug", "info", "warn", "error"];
         if !valid_log_levels.contains(&self.logging.level.as_str()) {
             return Err(anyhow!("logging.level must be one of: debug, info, warn, error"));
         }
 
+        if self.analytics.dsn.is_some() {
+            if self.analytics.batch_max_interval_ms == 0 {
+                return Err(anyhow!("analytics.batchMaxIntervalMs must be greater than 0 when analytics.dsn is set"));
+            }
+            if self.analytics.batch_max_events == 0 {
+                return Err(anyhow!("analytics.batchMaxEvents must be greater than 0 when analytics.dsn is set"));
+            }
+        }
+
         Ok(())
     }
 }
 
 fn expand_path(path: &str) -> String {
-    if path.starts_with("~/") {
+    if let Some(rest) = path.strip_prefix("~/") {
         if let Some(home_dir) = dirs::home_dir() {
-            return path.replacen("~", &home_dir.to_string_lossy(), 1);
+            return home_dir.join(rest).to_string_lossy().to_string();
         }
     }
-    
-    // Expand environment variables
-    let expanded = env::var(path).unwrap_or_else(|_| path.to_string());
-    expanded
+
+    path.to_string()
 }
 
 mod dirs {
@@ -195,3 +302,61 @@ mod dirs {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn write_json(contents: &str) -> NamedTempFile {
+        let file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        std::fs::write(file.path(), contents).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_load_fills_in_defaults_when_config_file_is_missing() {
+        let mut overrides = HashMap::new();
+        overrides.insert("apiKey".to_string(), "test-key".to_string());
+
+        let config = Config::load("/nonexistent/collector.json", &[], &overrides).unwrap();
+        assert_eq!(config.backend_url, Config::default().backend_url);
+        assert_eq!(config.buffer.backend, "sqlite");
+    }
+
+    #[test]
+    fn test_load_layers_extra_sources_in_order() {
+        let base = write_json(r#"{"apiKey":"base-key","projectId":"base-project"}"#);
+        let overlay = write_json(r#"{"projectId":"overlay-project"}"#);
+
+        let config = Config::load(
+            base.path().to_str().unwrap(),
+            &[overlay.path().to_str().unwrap().to_string()],
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        // The overlay (later source) wins over the base file, and the base file's own field
+        // that the overlay doesn't touch survives the merge.
+        assert_eq!(config.project_id, "overlay-project");
+        assert_eq!(config.api_key, "base-key");
+    }
+
+    #[test]
+    fn test_load_cli_overrides_beat_every_file_source() {
+        let base = write_json(r#"{"backendUrl":"http://from-file.example","apiKey":"test-key"}"#);
+
+        let mut overrides = HashMap::new();
+        overrides.insert("backendUrl".to_string(), "http://from-cli.example".to_string());
+
+        let config = Config::load(base.path().to_str().unwrap(), &[], &overrides).unwrap();
+        assert_eq!(config.backend_url, "http://from-cli.example");
+    }
+
+    #[test]
+    fn test_load_runs_validate_after_the_full_merge() {
+        let base = write_json(r#"{"collection":{"batchSize":0}}"#);
+        let result = Config::load(base.path().to_str().unwrap(), &[], &HashMap::new());
+        assert!(result.is_err());
+    }
+}
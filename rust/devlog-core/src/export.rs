@@ -0,0 +1,350 @@
+//! Streaming export/import of `AgentEvent`s to a normalized on-disk form, so a parsed session
+//! can be persisted or shipped between tools without re-parsing the original agent log.
+use crate::AgentEvent;
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Version of the MessagePack record layout, written as a single header byte so a reader can
+/// detect an incompatible `AgentEvent` schema change before decoding any records.
+const MSGPACK_SCHEMA_VERSION: u8 = 1;
+
+/// On-disk encoding for an exported event stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFormat {
+    /// One JSON object per line. Human-readable, larger on disk.
+    Jsonl,
+    /// Length-prefixed MessagePack records behind a single schema-version header byte.
+    MessagePack,
+}
+
+/// Destination for a stream of `AgentEvent`s, written one at a time so callers never need to
+/// hold a whole session in memory to export it.
+pub trait EventSink {
+    fn write_event(&mut self, event: &AgentEvent) -> Result<()>;
+
+    /// Flush any trailing state (e.g. a header that must be written even for an empty stream).
+    /// The default is a no-op; sinks with nothing to flush can ignore this.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A previously exported event stream, read back one event at a time.
+pub trait EventSource {
+    fn read_event(&mut self) -> Result<Option<AgentEvent>>;
+}
+
+/// Newline-delimited JSON sink.
+pub struct JsonlSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonlSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> EventSink for JsonlSink<W> {
+    fn write_event(&mut self, event: &AgentEvent) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, event).context("failed to serialize event as JSON")?;
+        self.writer.write_all(b"\n").context("failed to write newline")?;
+        Ok(())
+    }
+}
+
+/// Newline-delimited JSON source, matching [`JsonlSink`].
+pub struct JsonlSource<R: Read> {
+    lines: std::io::Lines<BufReader<R>>,
+}
+
+impl<R: Read> JsonlSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: BufReader::new(reader).lines(),
+        }
+    }
+}
+
+impl<R: Read> EventSource for JsonlSource<R> {
+    fn read_event(&mut self) -> Result<Option<AgentEvent>> {
+        loop {
+            return match self.lines.next() {
+                Some(line) => {
+                    let line = line.context("failed to read line")?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    Ok(Some(serde_json::from_str(&line).context("failed to parse event JSON")?))
+                }
+                None => Ok(None),
+            };
+        }
+    }
+}
+
+/// Compact binary sink: a one-byte schema version header followed by `u32`-length-prefixed
+/// MessagePack records, one per event.
+pub struct MessagePackSink<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W: Write> MessagePackSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+        }
+    }
+
+    fn ensure_header(&mut self) -> Result<()> {
+        if !self.header_written {
+            self.writer
+                .write_all(&[MSGPACK_SCHEMA_VERSION])
+                .context("failed to write schema version header")?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> EventSink for MessagePackSink<W> {
+    fn write_event(&mut self, event: &AgentEvent) -> Result<()> {
+        self.ensure_header()?;
+        let record = rmp_serde::to_vec_named(event).context("failed to encode event as MessagePack")?;
+        self.writer
+            .write_all(&(record.len() as u32).to_le_bytes())
+            .context("failed to write record length")?;
+        self.writer.write_all(&record).context("failed to write MessagePack record")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        // An empty stream still needs the version header, otherwise the file is indistinguishable
+        // from an empty/corrupt one on read-back.
+        self.ensure_header()
+    }
+}
+
+/// Compact binary source, matching [`MessagePackSink`].
+pub struct MessagePackSource<R: Read> {
+    reader: R,
+    header_checked: bool,
+}
+
+impl<R: Read> MessagePackSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            header_checked: false,
+        }
+    }
+
+    fn check_header(&mut self) -> Result<()> {
+        if self.header_checked {
+            return Ok(());
+        }
+
+        let mut version = [0u8; 1];
+        self.reader.read_exact(&mut version).context("failed to read schema version header")?;
+        if version[0] != MSGPACK_SCHEMA_VERSION {
+            return Err(anyhow!(
+                "unsupported MessagePack event stream schema version {} (expected {})",
+                version[0],
+                MSGPACK_SCHEMA_VERSION
+            ));
+        }
+        self.header_checked = true;
+        Ok(())
+    }
+}
+
+impl<R: Read> EventSource for MessagePackSource<R> {
+    fn read_event(&mut self) -> Result<Option<AgentEvent>> {
+        self.check_header()?;
+
+        let mut len_bytes = [0u8; 4];
+        match self.reader.read_exact(&mut len_bytes) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e).context("failed to read record length"),
+        }
+
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        let mut record = vec![0u8; len];
+        self.reader.read_exact(&mut record).context("failed to read MessagePack record")?;
+        let event = rmp_serde::from_slice(&record).context("failed to decode MessagePack event")?;
+        Ok(Some(event))
+    }
+}
+
+/// Write `events` to `path` in `format`, handing each event to the sink as it's produced rather
+/// than collecting a second in-memory copy of the whole session first.
+pub fn write_events(path: &Path, format: EventFormat, events: impl IntoIterator<Item = AgentEvent>) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let writer = BufWriter::new(file);
+
+    match format {
+        EventFormat::Jsonl => {
+            let mut sink = JsonlSink::new(writer);
+            for event in events {
+                sink.write_event(&event)?;
+            }
+            sink.finish()
+        }
+        EventFormat::MessagePack => {
+            let mut sink = MessagePackSink::new(writer);
+            for event in events {
+                sink.write_event(&event)?;
+            }
+            sink.finish()
+        }
+    }
+}
+
+/// Read back every event previously written to `path` by [`write_events`] in `format`.
+pub fn read_events(path: &Path, format: EventFormat) -> Result<Vec<AgentEvent>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let reader = BufReader::new(file);
+
+    let mut events = Vec::new();
+    match format {
+        EventFormat::Jsonl => {
+            let mut source = JsonlSource::new(reader);
+            while let Some(event) = source.read_event()? {
+                events.push(event);
+            }
+        }
+        EventFormat::MessagePack => {
+            let mut source = MessagePackSource::new(reader);
+            while let Some(event) = source.read_event()? {
+                events.push(event);
+            }
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EVENT_TYPE_LLM_REQUEST;
+    use std::collections::HashMap;
+    use tempfile::NamedTempFile;
+
+    fn sample_event(id: &str) -> AgentEvent {
+        AgentEvent {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now(),
+            event_type: EVENT_TYPE_LLM_REQUEST.to_string(),
+            agent_id: "test-agent".to_string(),
+            agent_version: "1.0.0".to_string(),
+            session_id: "sess_123".to_string(),
+            project_id: 1,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::new(),
+            data: HashMap::new(),
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let file = NamedTempFile::new().unwrap();
+        let events = vec![sample_event("1"), sample_event("2")];
+
+        write_events(file.path(), EventFormat::Jsonl, events.clone()).unwrap();
+        let read_back = read_events(file.path(), EventFormat::Jsonl).unwrap();
+
+        assert_eq!(read_back.len(), 2);
+        assert_eq!(read_back[0].id, "1");
+        assert_eq!(read_back[1].id, "2");
+    }
+
+    #[test]
+    fn test_messagepack_round_trip() {
+        let file = NamedTempFile::new().unwrap();
+        let events = vec![sample_event("1"), sample_event("2")];
+
+        write_events(file.path(), EventFormat::MessagePack, events.clone()).unwrap();
+        let read_back = read_events(file.path(), EventFormat::MessagePack).unwrap();
+
+        assert_eq!(read_back.len(), 2);
+        assert_eq!(read_back[0].id, "1");
+        assert_eq!(read_back[1].id, "2");
+    }
+
+    #[test]
+    fn test_messagepack_is_smaller_than_jsonl_for_a_real_session() {
+        let jsonl_file = NamedTempFile::new().unwrap();
+        let msgpack_file = NamedTempFile::new().unwrap();
+        let events: Vec<AgentEvent> = (0..50).map(|i| sample_event(&i.to_string())).collect();
+
+        write_events(jsonl_file.path(), EventFormat::Jsonl, events.clone()).unwrap();
+        write_events(msgpack_file.path(), EventFormat::MessagePack, events).unwrap();
+
+        let jsonl_size = std::fs::metadata(jsonl_file.path()).unwrap().len();
+        let msgpack_size = std::fs::metadata(msgpack_file.path()).unwrap().len();
+        assert!(msgpack_size < jsonl_size);
+    }
+
+    #[test]
+    fn test_jsonl_round_trip_preserves_metrics_context_and_data() {
+        let file = NamedTempFile::new().unwrap();
+        let mut event = sample_event("1");
+        event.context.insert("logLevel".to_string(), serde_json::json!("INFO"));
+        event.data.insert("toolName".to_string(), serde_json::json!("bash"));
+        event.metrics = Some(crate::EventMetrics {
+            token_count: Some(120),
+            duration_ms: Some(450),
+            prompt_tokens: Some(80),
+            response_tokens: Some(40),
+            cost: Some(0.0042),
+        });
+
+        write_events(file.path(), EventFormat::Jsonl, vec![event.clone()]).unwrap();
+        let read_back = read_events(file.path(), EventFormat::Jsonl).unwrap();
+
+        assert_eq!(read_back.len(), 1);
+        assert_eq!(read_back[0].context, event.context);
+        assert_eq!(read_back[0].data, event.data);
+        assert_eq!(read_back[0].metrics, event.metrics);
+    }
+
+    #[test]
+    fn test_messagepack_round_trip_preserves_metrics_context_and_data() {
+        let file = NamedTempFile::new().unwrap();
+        let mut event = sample_event("1");
+        event.context.insert("logLevel".to_string(), serde_json::json!("INFO"));
+        event.data.insert("toolName".to_string(), serde_json::json!("bash"));
+        event.metrics = Some(crate::EventMetrics {
+            token_count: Some(120),
+            duration_ms: Some(450),
+            prompt_tokens: Some(80),
+            response_tokens: Some(40),
+            cost: Some(0.0042),
+        });
+
+        write_events(file.path(), EventFormat::MessagePack, vec![event.clone()]).unwrap();
+        let read_back = read_events(file.path(), EventFormat::MessagePack).unwrap();
+
+        assert_eq!(read_back.len(), 1);
+        assert_eq!(read_back[0].context, event.context);
+        assert_eq!(read_back[0].data, event.data);
+        assert_eq!(read_back[0].metrics, event.metrics);
+    }
+
+    #[test]
+    fn test_messagepack_rejects_unknown_schema_version() {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), [MSGPACK_SCHEMA_VERSION + 1]).unwrap();
+
+        let result = read_events(file.path(), EventFormat::MessagePack);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// This collector's protocol version. Bump `major` for wire-incompatible changes (fields a
+/// backend on the old major could no longer parse), `minor` for additive ones (new event types
+/// or fields an older backend simply won't recognize yet).
+pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl ProtocolVersion {
+    /// Majors must match exactly; a minor mismatch just means some capabilities may be missing,
+    /// which `Capabilities` already degrades gracefully rather than refusing the connection.
+    pub fn is_compatible_with(&self, other: &ProtocolVersion) -> bool {
+        self.major == other.major
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// What a backend advertised it can accept during the version handshake, used to adapt outgoing
+/// collection instead of sending data the backend would reject wholesale. An empty set for
+/// `supported_event_types`/`accepted_fields` means the backend didn't advertise a restriction on
+/// that axis, so everything passes — this is also `Capabilities::default()`, used when no
+/// handshake has happened yet, preserving today's unrestricted behavior.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    #[serde(default)]
+    pub supported_event_types: HashSet<String>,
+    #[serde(default)]
+    pub accepted_fields: HashSet<String>,
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: i32,
+}
+
+fn default_max_batch_size() -> i32 {
+    i32::MAX
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            supported_event_types: HashSet::new(),
+            accepted_fields: HashSet::new(),
+            max_batch_size: default_max_batch_size(),
+        }
+    }
+}
+
+impl Capabilities {
+    pub fn supports_event_type(&self, event_type: &str) -> bool {
+        self.supported_event_types.is_empty() || self.supported_event_types.contains(event_type)
+    }
+
+    pub fn accepts_field(&self, field: &str) -> bool {
+        self.accepted_fields.is_empty() || self.accepted_fields.contains(field)
+    }
+
+    /// Adapt `event` in place to the negotiated capabilities. Returns `false` if `event_type`
+    /// itself isn't supported, meaning the caller should drop the event entirely rather than
+    /// send it; otherwise strips `metrics` subfields the backend didn't advertise support for
+    /// (downgrading rather than dropping the whole event over one unsupported field).
+    pub fn apply(&self, event: &mut crate::AgentEvent) -> bool {
+        if !self.supports_event_type(&event.event_type) {
+            return false;
+        }
+
+        if !self.accepts_field("metrics") {
+            event.metrics = None;
+        } else if let Some(metrics) = &mut event.metrics {
+            if !self.accepts_field("metrics.tokenCount") {
+                metrics.token_count = None;
+            }
+            if !self.accepts_field("metrics.durationMs") {
+                metrics.duration_ms = None;
+            }
+            if !self.accepts_field("metrics.promptTokens") {
+                metrics.prompt_tokens = None;
+            }
+            if !self.accepts_field("metrics.responseTokens") {
+                metrics.response_tokens = None;
+            }
+            if !self.accepts_field("metrics.cost") {
+                metrics.cost = None;
+            }
+        }
+
+        true
+    }
+}
+
+/// Body of the backend's `GET /api/version` (or `POST /handshake`) response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandshakeResponse {
+    pub protocol_version: ProtocolVersion,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
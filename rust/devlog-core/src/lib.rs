@@ -2,7 +2,19 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
+pub mod analytics;
 pub mod config;
+pub mod export;
+pub mod handshake;
+pub mod lifecycle;
+pub mod query;
+pub mod session;
+
+pub use analytics::MetricsAggregator;
+pub use export::{read_events, write_events, EventFormat};
+pub use lifecycle::{summarize_session, SessionState, SessionSummary};
+pub use query::{BatchIterator, Selectors, StreamMode, StreamParameters};
+pub use session::SessionReconstructor;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -32,7 +44,7 @@ pub struct AgentEvent {
     pub metrics: Option<EventMetrics>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct EventMetrics {
     #[serde(skip_serializing_if = "Option::is_none")]
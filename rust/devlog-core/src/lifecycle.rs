@@ -0,0 +1,213 @@
+//! Derives a per-session lifecycle state and cost/activity rollup from a session's flat
+//! `AgentEvent` stream. A complementary view to [`crate::session::SessionReconstructor`]'s
+//! request/tool/response turn-pairing: that module answers "what did each turn look like", this
+//! one answers "what state is this session in right now, and what did it cost".
+use crate::{
+    AgentEvent, EVENT_TYPE_COMMAND_EXEC, EVENT_TYPE_ERROR, EVENT_TYPE_FILE_MODIFY,
+    EVENT_TYPE_FILE_READ, EVENT_TYPE_FILE_WRITE, EVENT_TYPE_LLM_REQUEST, EVENT_TYPE_LLM_RESPONSE,
+    EVENT_TYPE_SESSION_END, EVENT_TYPE_SESSION_START, EVENT_TYPE_TOOL_USE, EVENT_TYPE_USER_INTERACTION,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where a session sits in its lifecycle, derived by folding its events in timestamp order: each
+/// lifecycle-relevant event type overwrites the current state, so the final value reflects the
+/// most recent one seen. `Executing` therefore holds from a `command_execution` event until
+/// whatever event follows it resolves the command (another `command_execution` restarts it,
+/// anything else moves the session on).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    Starting,
+    Active,
+    WaitingOnUser,
+    Executing,
+    Errored,
+    Ended,
+}
+
+/// Rollup of a session's token/cost totals, tool usage, file activity, and errors, alongside its
+/// derived [`SessionState`]. Built by [`summarize_session`] from a session's raw events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub state: SessionState,
+    pub prompt_tokens: i64,
+    pub response_tokens: i64,
+    pub total_cost: f64,
+    pub tool_use_counts: HashMap<String, u64>,
+    pub files_read: u64,
+    pub files_written: u64,
+    pub files_modified: u64,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub errors: Vec<AgentEvent>,
+}
+
+/// Fold `events` into a [`SessionSummary`] for `session_id`. Events are sorted by timestamp
+/// before folding, so callers don't need to pre-sort whatever order the `EventStore` returned
+/// them in. `events` is expected to already be scoped to one session (e.g. via
+/// `QueryParams.session_id`); events from other sessions are folded in anyway rather than
+/// rejected, since filtering is the caller's responsibility.
+pub fn summarize_session(session_id: &str, events: &[AgentEvent]) -> SessionSummary {
+    let mut sorted: Vec<&AgentEvent> = events.iter().collect();
+    sorted.sort_by_key(|event| event.timestamp);
+
+    let mut summary = SessionSummary {
+        session_id: session_id.to_string(),
+        state: SessionState::Starting,
+        prompt_tokens: 0,
+        response_tokens: 0,
+        total_cost: 0.0,
+        tool_use_counts: HashMap::new(),
+        files_read: 0,
+        files_written: 0,
+        files_modified: 0,
+        start_time: None,
+        end_time: None,
+        duration_ms: None,
+        errors: Vec::new(),
+    };
+
+    for event in sorted {
+        summary.start_time = Some(summary.start_time.map_or(event.timestamp, |t| t.min(event.timestamp)));
+        summary.end_time = Some(summary.end_time.map_or(event.timestamp, |t| t.max(event.timestamp)));
+
+        if let Some(metrics) = &event.metrics {
+            summary.prompt_tokens += metrics.prompt_tokens.unwrap_or(0) as i64;
+            summary.response_tokens += metrics.response_tokens.unwrap_or(0) as i64;
+            summary.total_cost += metrics.cost.unwrap_or(0.0);
+        }
+
+        match event.event_type.as_str() {
+            EVENT_TYPE_SESSION_START => summary.state = SessionState::Starting,
+            EVENT_TYPE_USER_INTERACTION => summary.state = SessionState::WaitingOnUser,
+            EVENT_TYPE_COMMAND_EXEC => summary.state = SessionState::Executing,
+            EVENT_TYPE_ERROR => {
+                summary.state = SessionState::Errored;
+                summary.errors.push(event.clone());
+            }
+            EVENT_TYPE_SESSION_END => summary.state = SessionState::Ended,
+            EVENT_TYPE_LLM_REQUEST | EVENT_TYPE_LLM_RESPONSE => summary.state = SessionState::Active,
+            EVENT_TYPE_TOOL_USE => {
+                summary.state = SessionState::Active;
+                if let Some(name) = event.data.get("toolName").and_then(|v| v.as_str()) {
+                    *summary.tool_use_counts.entry(name.to_string()).or_insert(0) += 1;
+                }
+            }
+            EVENT_TYPE_FILE_READ => {
+                summary.files_read += 1;
+                summary.state = SessionState::Active;
+            }
+            EVENT_TYPE_FILE_WRITE => {
+                summary.files_written += 1;
+                summary.state = SessionState::Active;
+            }
+            EVENT_TYPE_FILE_MODIFY => {
+                summary.files_modified += 1;
+                summary.state = SessionState::Active;
+            }
+            _ => {}
+        }
+    }
+
+    if let (Some(start), Some(end)) = (summary.start_time, summary.end_time) {
+        summary.duration_ms = Some((end - start).num_milliseconds());
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap as Map;
+
+    fn make_event(event_type: &str, offset_secs: i64, data: Map<String, serde_json::Value>) -> AgentEvent {
+        AgentEvent {
+            id: format!("e-{}", offset_secs),
+            timestamp: Utc::now() + chrono::Duration::seconds(offset_secs),
+            event_type: event_type.to_string(),
+            agent_id: "claude".to_string(),
+            agent_version: "1.0".to_string(),
+            session_id: "s1".to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: Map::new(),
+            data,
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_ends_in_ended_state_after_session_end() {
+        let events = vec![
+            make_event(EVENT_TYPE_SESSION_START, 0, Map::new()),
+            make_event(EVENT_TYPE_TOOL_USE, 1, Map::new()),
+            make_event(EVENT_TYPE_SESSION_END, 2, Map::new()),
+        ];
+        let summary = summarize_session("s1", &events);
+        assert_eq!(summary.state, SessionState::Ended);
+        assert_eq!(summary.duration_ms, Some(2000));
+    }
+
+    #[test]
+    fn test_counts_tool_use_and_file_activity() {
+        let mut tool_data = Map::new();
+        tool_data.insert("toolName".to_string(), json!("search"));
+
+        let events = vec![
+            make_event(EVENT_TYPE_SESSION_START, 0, Map::new()),
+            make_event(EVENT_TYPE_TOOL_USE, 1, tool_data),
+            make_event(EVENT_TYPE_FILE_READ, 2, Map::new()),
+            make_event(EVENT_TYPE_FILE_WRITE, 3, Map::new()),
+        ];
+        let summary = summarize_session("s1", &events);
+        assert_eq!(summary.tool_use_counts["search"], 1);
+        assert_eq!(summary.files_read, 1);
+        assert_eq!(summary.files_written, 1);
+        assert_eq!(summary.state, SessionState::Active);
+    }
+
+    #[test]
+    fn test_errored_state_collects_error_events() {
+        let events = vec![
+            make_event(EVENT_TYPE_SESSION_START, 0, Map::new()),
+            make_event(EVENT_TYPE_ERROR, 1, Map::new()),
+        ];
+        let summary = summarize_session("s1", &events);
+        assert_eq!(summary.state, SessionState::Errored);
+        assert_eq!(summary.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_user_interaction_moves_session_to_waiting_on_user() {
+        let events = vec![
+            make_event(EVENT_TYPE_SESSION_START, 0, Map::new()),
+            make_event(EVENT_TYPE_USER_INTERACTION, 1, Map::new()),
+        ];
+        let summary = summarize_session("s1", &events);
+        assert_eq!(summary.state, SessionState::WaitingOnUser);
+    }
+
+    #[test]
+    fn test_command_execution_moves_session_to_executing_until_the_next_event() {
+        let events = vec![
+            make_event(EVENT_TYPE_SESSION_START, 0, Map::new()),
+            make_event(EVENT_TYPE_COMMAND_EXEC, 1, Map::new()),
+        ];
+        let summary = summarize_session("s1", &events);
+        assert_eq!(summary.state, SessionState::Executing);
+
+        let mut events_with_followup = events;
+        events_with_followup.push(make_event(EVENT_TYPE_FILE_WRITE, 2, Map::new()));
+        let summary = summarize_session("s1", &events_with_followup);
+        assert_eq!(summary.state, SessionState::Active);
+    }
+}
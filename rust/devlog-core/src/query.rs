@@ -0,0 +1,241 @@
+//! Selector-based filtering and size-bounded batching for reading `AgentEvent`s back out of a
+//! buffer, used by the collector server's query/stream endpoint.
+use crate::AgentEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Default batch size target, in serialized bytes, for [`BatchIterator`].
+pub const DEFAULT_CHUNK_SIZE_TARGET: usize = 64 * 1024;
+
+/// What a query streams. Kept as an enum (rather than hard-coding events) so the selector model
+/// can grow additional data types later without changing `StreamParameters`'s shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataType {
+    Events,
+}
+
+/// How long a query stays open and what it emits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamMode {
+    /// Drain everything currently buffered, then end.
+    Snapshot,
+    /// Emit nothing buffered; only events arriving after connect.
+    Subscribe,
+    /// Snapshot first, then seamlessly continue as `Subscribe`.
+    SnapshotThenSubscribe,
+}
+
+/// Output encoding for a batch of events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamFormat {
+    Json,
+}
+
+/// One `context`/`data` field a selector requires to be present and glob-match `pattern`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldSelector {
+    pub key: String,
+    pub pattern: String,
+}
+
+impl FieldSelector {
+    fn matches(&self, fields: &HashMap<String, Value>) -> bool {
+        match fields.get(&self.key) {
+            Some(value) => glob_match(&self.pattern, &value_to_string(value)),
+            None => false,
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Trailing-`*` glob match, the same convention `devlog_adapters::pricing::PricingTable` uses
+/// for model-name patterns.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+/// Glob-filterable criteria an `AgentEvent` must satisfy to be included in a query's output.
+/// `agent_id`/`event_type`/`session_id` accept a trailing `*` wildcard (e.g. `claude:*`);
+/// `context`/`data` selectors require the named key to be present with a matching value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Selectors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<FieldSelector>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data: Vec<FieldSelector>,
+}
+
+impl Selectors {
+    pub fn matches(&self, event: &AgentEvent) -> bool {
+        if let Some(ref pattern) = self.agent_id {
+            if !glob_match(pattern, &event.agent_id) {
+                return false;
+            }
+        }
+        if let Some(ref pattern) = self.event_type {
+            if !glob_match(pattern, &event.event_type) {
+                return false;
+            }
+        }
+        if let Some(ref pattern) = self.session_id {
+            if !glob_match(pattern, &event.session_id) {
+                return false;
+            }
+        }
+        self.context.iter().all(|f| f.matches(&event.context)) && self.data.iter().all(|f| f.matches(&event.data))
+    }
+}
+
+fn default_format() -> StreamFormat {
+    StreamFormat::Json
+}
+
+fn default_chunk_size_target() -> usize {
+    DEFAULT_CHUNK_SIZE_TARGET
+}
+
+/// Parameters for a query/stream request: what to stream, how long to stay open, which events
+/// to include, and how to batch the output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamParameters {
+    pub data_type: DataType,
+    pub stream_mode: StreamMode,
+    #[serde(default)]
+    pub selectors: Selectors,
+    #[serde(default = "default_format")]
+    pub format: StreamFormat,
+    #[serde(default = "default_chunk_size_target")]
+    pub chunk_size_target: usize,
+}
+
+/// Accumulates matching events into batches, flushing once the serialized size of the pending
+/// batch reaches `chunk_size_target`. Turns a potentially huge query result into a handful of
+/// bounded chunks instead of one unbounded response.
+pub struct BatchIterator {
+    chunk_size_target: usize,
+    pending: Vec<AgentEvent>,
+    pending_size: usize,
+}
+
+impl BatchIterator {
+    pub fn new(chunk_size_target: usize) -> Self {
+        Self {
+            chunk_size_target: chunk_size_target.max(1),
+            pending: Vec::new(),
+            pending_size: 0,
+        }
+    }
+
+    /// Add `event` to the pending batch. Returns `Some(batch)` once adding it pushes the
+    /// batch's serialized size to or past the target, in which case the batch should be
+    /// flushed and a new one started; returns `None` while still accumulating.
+    pub fn push(&mut self, event: AgentEvent) -> Option<Vec<AgentEvent>> {
+        self.pending_size += serde_json::to_string(&event).map(|s| s.len()).unwrap_or(0);
+        self.pending.push(event);
+
+        if self.pending_size >= self.chunk_size_target {
+            self.pending_size = 0;
+            Some(std::mem::take(&mut self.pending))
+        } else {
+            None
+        }
+    }
+
+    /// Flush and return whatever remains in the pending batch (possibly empty — an empty
+    /// flush is how a `Snapshot` stream signals end-of-stream).
+    pub fn flush(&mut self) -> Vec<AgentEvent> {
+        self.pending_size = 0;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EVENT_TYPE_LLM_REQUEST;
+
+    fn sample_event(agent_id: &str) -> AgentEvent {
+        AgentEvent {
+            id: "evt-1".to_string(),
+            timestamp: chrono::Utc::now(),
+            event_type: EVENT_TYPE_LLM_REQUEST.to_string(),
+            agent_id: agent_id.to_string(),
+            agent_version: "".to_string(),
+            session_id: "sess_1".to_string(),
+            project_id: 1,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::from([("model".to_string(), Value::String("claude-3-5-sonnet".to_string()))]),
+            data: HashMap::new(),
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_selectors_glob_matches_agent_id_prefix() {
+        let selectors = Selectors {
+            agent_id: Some("claude:*".to_string()),
+            ..Default::default()
+        };
+
+        assert!(selectors.matches(&sample_event("claude:main")));
+        assert!(!selectors.matches(&sample_event("cursor:main")));
+    }
+
+    #[test]
+    fn test_selectors_context_field_requires_matching_value() {
+        let selectors = Selectors {
+            context: vec![FieldSelector {
+                key: "model".to_string(),
+                pattern: "claude-3-5*".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        assert!(selectors.matches(&sample_event("claude:main")));
+
+        let selectors = Selectors {
+            context: vec![FieldSelector {
+                key: "missing".to_string(),
+                pattern: "*".to_string(),
+            }],
+            ..Default::default()
+        };
+        assert!(!selectors.matches(&sample_event("claude:main")));
+    }
+
+    #[test]
+    fn test_batch_iterator_flushes_at_target_size_and_on_demand() {
+        let mut batch = BatchIterator::new(1);
+        let flushed = batch.push(sample_event("claude:main"));
+        assert_eq!(flushed.unwrap().len(), 1);
+
+        let mut batch = BatchIterator::new(DEFAULT_CHUNK_SIZE_TARGET);
+        assert!(batch.push(sample_event("claude:main")).is_none());
+        assert_eq!(batch.flush().len(), 1);
+        assert_eq!(batch.flush().len(), 0);
+    }
+}
@@ -0,0 +1,230 @@
+//! Post-processing pass that groups flat `AgentEvent`s into `Session`s of request/response
+//! turns, pairing interleaved tool calls the way a multi-step function-calling model does.
+use crate::{AgentEvent, EventMetrics, EVENT_TYPE_LLM_REQUEST, EVENT_TYPE_LLM_RESPONSE, EVENT_TYPE_TOOL_USE};
+use std::collections::HashMap;
+
+/// Session id used to bucket events that don't carry one of their own.
+const UNASSIGNED_SESSION_ID: &str = "__unassigned__";
+
+/// A `tool_use` event paired with the nearest following event sharing its tool identity
+/// (`toolId` if present, else `toolName`), treated as that call's result.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub invocation: AgentEvent,
+    pub result: Option<AgentEvent>,
+}
+
+/// One request/response turn, with any tool calls made in between. `request`/`response` are
+/// `None` for a turn that never got a matching counterpart (e.g. an open turn at end of log).
+#[derive(Debug, Clone, Default)]
+pub struct Turn {
+    pub request: Option<AgentEvent>,
+    pub tool_calls: Vec<ToolCall>,
+    pub response: Option<AgentEvent>,
+    pub prompt_tokens: i32,
+    pub response_tokens: i32,
+    pub cost: f64,
+    pub duration_ms: Option<i64>,
+}
+
+impl Turn {
+    /// A turn is open when it has a request but no terminating response yet.
+    pub fn is_open(&self) -> bool {
+        self.request.is_some() && self.response.is_none()
+    }
+}
+
+/// A reconstructed conversation: all turns for one `session_id`, in timestamp order.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub session_id: String,
+    pub turns: Vec<Turn>,
+}
+
+pub struct SessionReconstructor;
+
+impl SessionReconstructor {
+    /// Group `events` by session, order each group by timestamp, and fold them into turns.
+    pub fn reconstruct(events: Vec<AgentEvent>) -> Vec<Session> {
+        let mut by_session: HashMap<String, Vec<AgentEvent>> = HashMap::new();
+        for event in events {
+            let key = if event.session_id.is_empty() {
+                UNASSIGNED_SESSION_ID.to_string()
+            } else {
+                event.session_id.clone()
+            };
+            by_session.entry(key).or_default().push(event);
+        }
+
+        let mut sessions: Vec<Session> = by_session
+            .into_iter()
+            .map(|(session_id, mut evs)| {
+                evs.sort_by_key(|e| e.timestamp);
+                let turns = Self::reconstruct_turns(evs);
+                Session { session_id, turns }
+            })
+            .collect();
+
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+
+    fn reconstruct_turns(events: Vec<AgentEvent>) -> Vec<Turn> {
+        let mut turns = Vec::new();
+        let mut current: Option<Turn> = None;
+        let mut pending_tools: Vec<AgentEvent> = Vec::new();
+
+        for event in events {
+            match event.event_type.as_str() {
+                EVENT_TYPE_LLM_REQUEST => {
+                    // A new request while one is already open means the prior one never got a
+                    // response: emit it as an open turn before starting the new one.
+                    if let Some(mut turn) = current.take() {
+                        turn.tool_calls = pair_tool_calls(std::mem::take(&mut pending_tools));
+                        turns.push(turn);
+                    }
+                    current = Some(Turn {
+                        request: Some(event),
+                        ..Default::default()
+                    });
+                }
+                EVENT_TYPE_TOOL_USE => {
+                    pending_tools.push(event);
+                }
+                EVENT_TYPE_LLM_RESPONSE => {
+                    let mut turn = current.take().unwrap_or_default();
+                    turn.tool_calls = pair_tool_calls(std::mem::take(&mut pending_tools));
+                    Self::close_turn(&mut turn, event);
+                    turns.push(turn);
+                }
+                _ => {}
+            }
+        }
+
+        // Anything left dangling (an open request, or tool calls with no request at all)
+        // still gets emitted rather than silently dropped.
+        if current.is_some() || !pending_tools.is_empty() {
+            let mut turn = current.take().unwrap_or_default();
+            turn.tool_calls = pair_tool_calls(pending_tools);
+            turns.push(turn);
+        }
+
+        turns
+    }
+
+    fn close_turn(turn: &mut Turn, mut response: AgentEvent) {
+        let prompt_tokens = turn
+            .request
+            .as_ref()
+            .and_then(|r| r.metrics.as_ref())
+            .and_then(|m| m.prompt_tokens)
+            .unwrap_or(0);
+        let response_tokens = response
+            .metrics
+            .as_ref()
+            .and_then(|m| m.response_tokens)
+            .unwrap_or(0);
+        let cost = turn.request.as_ref().and_then(|r| r.metrics.as_ref()).and_then(|m| m.cost).unwrap_or(0.0)
+            + response.metrics.as_ref().and_then(|m| m.cost).unwrap_or(0.0);
+
+        let duration_ms = turn
+            .request
+            .as_ref()
+            .map(|r| (response.timestamp - r.timestamp).num_milliseconds());
+
+        if let Some(duration_ms) = duration_ms {
+            let metrics = response.metrics.get_or_insert_with(EventMetrics::default);
+            metrics.duration_ms = Some(duration_ms);
+        }
+
+        turn.prompt_tokens = prompt_tokens;
+        turn.response_tokens = response_tokens;
+        turn.cost = cost;
+        turn.duration_ms = duration_ms;
+        turn.response = Some(response);
+    }
+}
+
+fn tool_identity(event: &AgentEvent) -> Option<String> {
+    event
+        .data
+        .get("toolId")
+        .and_then(|v| v.as_str())
+        .or_else(|| event.data.get("toolName").and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+fn pair_tool_calls(tool_events: Vec<AgentEvent>) -> Vec<ToolCall> {
+    let mut calls: Vec<ToolCall> = Vec::new();
+    for event in tool_events {
+        let identity = tool_identity(&event);
+        let pending = calls
+            .iter_mut()
+            .rev()
+            .find(|c| c.result.is_none() && identity.is_some() && tool_identity(&c.invocation) == identity);
+
+        match pending {
+            Some(call) => call.result = Some(event),
+            None => calls.push(ToolCall { invocation: event, result: None }),
+        }
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use chrono::{Duration, Utc};
+
+    fn make_event(event_type: &str, session_id: &str, offset_secs: i64, data: HashMap<String, serde_json::Value>) -> AgentEvent {
+        AgentEvent {
+            id: format!("{event_type}-{offset_secs}"),
+            timestamp: Utc::now() + Duration::seconds(offset_secs),
+            event_type: event_type.to_string(),
+            agent_id: "claude".to_string(),
+            agent_version: "1.0".to_string(),
+            session_id: session_id.to_string(),
+            project_id: 0,
+            machine_id: None,
+            workspace_id: None,
+            legacy_project_id: None,
+            context: HashMap::new(),
+            data,
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_pairs_request_tool_and_response_into_one_turn() {
+        let events = vec![
+            make_event(EVENT_TYPE_LLM_REQUEST, "s1", 0, HashMap::new()),
+            make_event(EVENT_TYPE_TOOL_USE, "s1", 1, HashMap::from([("toolName".to_string(), json!("search"))])),
+            make_event(EVENT_TYPE_LLM_RESPONSE, "s1", 2, HashMap::new()),
+        ];
+
+        let sessions = SessionReconstructor::reconstruct(events);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].turns.len(), 1);
+        let turn = &sessions[0].turns[0];
+        assert!(turn.request.is_some());
+        assert!(turn.response.is_some());
+        assert_eq!(turn.tool_calls.len(), 1);
+        assert_eq!(turn.duration_ms, Some(2000));
+    }
+
+    #[test]
+    fn test_unterminated_turn_stays_open() {
+        let events = vec![make_event(EVENT_TYPE_LLM_REQUEST, "s1", 0, HashMap::new())];
+        let sessions = SessionReconstructor::reconstruct(events);
+        assert_eq!(sessions[0].turns.len(), 1);
+        assert!(sessions[0].turns[0].is_open());
+    }
+
+    #[test]
+    fn test_events_without_session_id_bucket_synthetically() {
+        let events = vec![make_event(EVENT_TYPE_LLM_REQUEST, "", 0, HashMap::new())];
+        let sessions = SessionReconstructor::reconstruct(events);
+        assert_eq!(sessions[0].session_id, UNASSIGNED_SESSION_ID);
+    }
+}
@@ -0,0 +1,60 @@
+//! Minimal HTTP/1.1 request/response I/O for the Docker plugin Unix socket. The plugin
+//! protocol is a handful of small JSON POSTs, so pulling in a full HTTP stack the way `server`
+//! does for the public collector API would be overkill here.
+use anyhow::{anyhow, Context, Result};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+pub struct PluginRequest {
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+/// Read one HTTP request off `stream`: the request line, headers (only `Content-Length` is
+/// consulted), and body.
+pub async fn read_request(stream: &mut UnixStream) -> Result<PluginRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await.context("failed to read request line")?;
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| anyhow!("malformed HTTP request line: {:?}", request_line))?
+        .to_string();
+
+    let mut content_length = 0usize;
+    loop {
+        let mut header_line = String::new();
+        reader.read_line(&mut header_line).await.context("failed to read header line")?;
+        let header_line = header_line.trim_end();
+        if header_line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header_line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    if content_length > 0 {
+        reader.read_exact(&mut body).await.context("failed to read request body")?;
+    }
+
+    Ok(PluginRequest { path, body })
+}
+
+/// Write a JSON response with the given status code back over `stream`.
+pub async fn write_json_response(stream: &mut UnixStream, status: u16, body: &[u8]) -> Result<()> {
+    let status_text = if status == 200 { "OK" } else { "Internal Server Error" };
+    let header = format!(
+        "HTTP/1.1 {status} {status_text}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await.context("failed to write response header")?;
+    stream.write_all(body).await.context("failed to write response body")?;
+    stream.flush().await.context("failed to flush response")?;
+    Ok(())
+}
@@ -0,0 +1,227 @@
+//! Docker logging-driver plugin: registers the collector as a Docker `LogDriver` so container
+//! stdout/stderr streams into the event store the same way file-based agent logs do via the
+//! `Watcher`, just via a different capture source.
+use anyhow::{Context, Result};
+use devlog_adapters::{AgentAdapter, Registry};
+use devlog_buffer::{EventStore, SqliteStore};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+mod http;
+mod logentry;
+mod protocol;
+
+use protocol::{ActivateResponse, PluginResult, StartLoggingRequest, StopLoggingRequest};
+
+/// Default location Docker looks for plugin sockets, per the plugin discovery spec.
+pub const DEFAULT_SOCKET_PATH: &str = "/run/docker/plugins/devlog.sock";
+
+pub struct Config {
+    pub registry: Arc<Registry>,
+    pub buffer: Arc<SqliteStore>,
+    pub socket_path: PathBuf,
+    /// Adapter used to parse reassembled container log lines. Containers don't self-identify
+    /// their agent format the way a file path or content sniff can, so this is fixed per server
+    /// rather than auto-detected.
+    pub adapter_name: String,
+}
+
+/// One in-flight `StartLogging` session's background FIFO reader, keyed by the FIFO path
+/// Docker gave us so a matching `StopLogging` can cancel it.
+type Sessions = Arc<Mutex<HashMap<String, JoinHandle<()>>>>;
+
+pub struct DockerPluginServer {
+    config: Arc<Config>,
+    sessions: Sessions,
+}
+
+impl DockerPluginServer {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config: Arc::new(config),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Serve the Docker `LogDriver` plugin API on a Unix domain socket until the process exits
+    /// or an accept error occurs.
+    pub async fn serve(self) -> Result<()> {
+        if let Some(parent) = self.config.socket_path.parent() {
+            tokio::fs::create_dir_all(parent).await.ok();
+        }
+        // Docker doesn't clean up the socket file from a previous run; a stale one left behind
+        // by an unclean shutdown would otherwise make `bind` fail with "address in use".
+        let _ = tokio::fs::remove_file(&self.config.socket_path).await;
+
+        let listener = UnixListener::bind(&self.config.socket_path)
+            .with_context(|| format!("failed to bind docker plugin socket at {}", self.config.socket_path.display()))?;
+        info!("Docker logging-driver plugin listening on {}", self.config.socket_path.display());
+
+        loop {
+            let (stream, _) = listener.accept().await.context("failed to accept docker plugin connection")?;
+            let config = self.config.clone();
+            let sessions = self.sessions.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, config, sessions).await {
+                    error!("docker plugin connection error: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(mut stream: UnixStream, config: Arc<Config>, sessions: Sessions) -> Result<()> {
+    let request = http::read_request(&mut stream).await?;
+
+    let (status, body) = match request.path.as_str() {
+        "/Plugin.Activate" => (200, serde_json::to_vec(&ActivateResponse::default())?),
+        "/LogDriver.StartLogging" => {
+            let req: StartLoggingRequest =
+                serde_json::from_slice(&request.body).context("invalid LogDriver.StartLogging request")?;
+            start_logging(req, config, sessions).await
+        }
+        "/LogDriver.StopLogging" => {
+            let req: StopLoggingRequest =
+                serde_json::from_slice(&request.body).context("invalid LogDriver.StopLogging request")?;
+            stop_logging(req, sessions).await
+        }
+        other => {
+            warn!("unsupported docker plugin path: {}", other);
+            (404, serde_json::to_vec(&PluginResult { err: Some(format!("unknown path {other}")) })?)
+        }
+    };
+
+    http::write_json_response(&mut stream, status, &body).await
+}
+
+async fn start_logging(req: StartLoggingRequest, config: Arc<Config>, sessions: Sessions) -> (u16, Vec<u8>) {
+    let adapter = match config.registry.get(&config.adapter_name) {
+        Some(adapter) => adapter,
+        None => {
+            let result = PluginResult {
+                err: Some(format!("unknown adapter: {}", config.adapter_name)),
+            };
+            return (500, serde_json::to_vec(&result).unwrap_or_default());
+        }
+    };
+
+    let fifo_path = PathBuf::from(&req.file);
+    let container_id = req.info.container_id.clone();
+    let buffer = config.buffer.clone();
+
+    let handle = tokio::spawn(async move {
+        if let Err(e) = follow_fifo(fifo_path, container_id.clone(), adapter, buffer).await {
+            error!("docker plugin: log session for container {} ended: {}", container_id, e);
+        }
+    });
+
+    sessions.lock().await.insert(req.file, handle);
+    (200, serde_json::to_vec(&PluginResult::default()).unwrap_or_default())
+}
+
+async fn stop_logging(req: StopLoggingRequest, sessions: Sessions) -> (u16, Vec<u8>) {
+    if let Some(handle) = sessions.lock().await.remove(&req.file) {
+        handle.abort();
+    }
+    (200, serde_json::to_vec(&PluginResult::default()).unwrap_or_default())
+}
+
+/// Read length-prefixed `LogEntry` frames from the FIFO Docker provided, reassemble partial
+/// lines (a `LogEntry` with `partial` set is a fragment, not a complete line), and feed each
+/// reconstructed line through `adapter.parse_log_line_at` into `buffer`, tagging it with an
+/// incrementing per-session ordinal so two identical lines from this container don't derive
+/// colliding event ids.
+async fn follow_fifo(fifo_path: PathBuf, container_id: String, adapter: Arc<dyn AgentAdapter>, buffer: Arc<SqliteStore>) -> Result<()> {
+    let file = tokio::fs::File::open(&fifo_path)
+        .await
+        .with_context(|| format!("failed to open docker log fifo {}", fifo_path.display()))?;
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut pending: Vec<u8> = Vec::new();
+    let mut ordinal: u32 = 0;
+
+    loop {
+        let mut len_bytes = [0u8; 4];
+        if reader.read_exact(&mut len_bytes).await.is_err() {
+            break; // FIFO closed: the container stopped logging.
+        }
+        let len = u32::from_be_bytes(len_bytes) as usize;
+
+        let mut frame = vec![0u8; len];
+        reader.read_exact(&mut frame).await.context("failed to read LogEntry frame body")?;
+
+        let entry = match logentry::decode(&frame) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("docker plugin: failed to decode LogEntry for container {}: {}", container_id, e);
+                continue;
+            }
+        };
+
+        let Some(line) = accumulate_line(&mut pending, &entry) else {
+            continue;
+        };
+
+        match adapter.parse_log_line_at(line.trim_end_matches('\n'), ordinal) {
+            Ok(Some(event)) => {
+                if let Err(e) = buffer.store(&event).await {
+                    error!("docker plugin: failed to store event for container {}: {}", container_id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("docker plugin: failed to parse line for container {}: {}", container_id, e),
+        }
+        ordinal = ordinal.wrapping_add(1);
+    }
+
+    Ok(())
+}
+
+/// Fold one decoded `LogEntry` into `pending`, returning the reassembled line once a non-`partial`
+/// entry completes it, or `None` while more partial fragments are still expected. Pulled out of
+/// `follow_fifo` so the reassembly logic can be exercised without a FIFO. UTF-8 decoding happens
+/// only once the full line is reassembled, not per-fragment, so a multi-byte character split
+/// across two `partial` frames doesn't get lossy-decoded mid-character.
+fn accumulate_line(pending: &mut Vec<u8>, entry: &logentry::LogEntry) -> Option<String> {
+    pending.extend_from_slice(&entry.line);
+    if entry.partial {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&std::mem::take(pending)).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logentry::LogEntry;
+
+    fn frame(line: &[u8], partial: bool) -> LogEntry {
+        LogEntry { source: "stdout".to_string(), time_nano: 0, line: line.to_vec(), partial }
+    }
+
+    #[test]
+    fn test_accumulate_line_waits_for_non_partial_entry() {
+        let mut pending = Vec::new();
+        assert_eq!(accumulate_line(&mut pending, &frame(b"hello ", true)), None);
+        assert_eq!(accumulate_line(&mut pending, &frame(b"world\n", false)), Some("hello world\n".to_string()));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_accumulate_line_reassembles_multibyte_utf8_split_across_partial_frames() {
+        // '€' (U+20AC) encodes as the 3 UTF-8 bytes [0xE2, 0x82, 0xAC]; split it across two
+        // `partial` frames so decoding either fragment alone would be lossy.
+        let euro = "€".as_bytes().to_vec();
+        let (first, second) = euro.split_at(1);
+
+        let mut pending = Vec::new();
+        assert_eq!(accumulate_line(&mut pending, &frame(first, true)), None);
+        let line = accumulate_line(&mut pending, &frame(second, false)).unwrap();
+        assert_eq!(line, "€");
+    }
+}
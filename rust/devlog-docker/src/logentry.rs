@@ -0,0 +1,160 @@
+//! Decoder for Docker's logging-driver protobuf `LogEntry` message
+//! (`github.com/docker/docker/api/types/plugins/logdriver`). A full protobuf codegen pipeline
+//! is unwarranted for the four fields this driver actually reads, so the wire format is decoded
+//! by hand:
+//!
+//! ```proto
+//! message LogEntry {
+//!   string source = 1;
+//!   int64 time_nano = 2;
+//!   bytes line = 3;
+//!   bool partial = 4;
+//!   PartialLogEntryMetadata partial_log_metadata = 5;
+//! }
+//! ```
+use anyhow::{anyhow, Result};
+
+/// One decoded `LogEntry` frame read from the Docker logging-driver FIFO.
+#[derive(Debug, Clone, Default)]
+pub struct LogEntry {
+    pub source: String,
+    pub time_nano: i64,
+    pub line: Vec<u8>,
+    pub partial: bool,
+}
+
+/// Decode a single protobuf-encoded `LogEntry` message body (the frame's length prefix has
+/// already been stripped by the caller).
+pub fn decode(buf: &[u8]) -> Result<LogEntry> {
+    let mut entry = LogEntry::default();
+    let mut pos = 0;
+
+    while pos < buf.len() {
+        let (tag, tag_len) = read_varint(&buf[pos..])?;
+        pos += tag_len;
+        let field_number = tag >> 3;
+        let wire_type = tag & 0x7;
+
+        match (field_number, wire_type) {
+            (1, 2) => {
+                let (bytes, consumed) = read_length_delimited(&buf[pos..])?;
+                entry.source = String::from_utf8_lossy(bytes).into_owned();
+                pos += consumed;
+            }
+            (2, 0) => {
+                let (value, consumed) = read_varint(&buf[pos..])?;
+                entry.time_nano = value as i64;
+                pos += consumed;
+            }
+            (3, 2) => {
+                let (bytes, consumed) = read_length_delimited(&buf[pos..])?;
+                entry.line = bytes.to_vec();
+                pos += consumed;
+            }
+            (4, 0) => {
+                let (value, consumed) = read_varint(&buf[pos..])?;
+                entry.partial = value != 0;
+                pos += consumed;
+            }
+            // Unknown/unneeded fields (e.g. `partial_log_metadata`) are skipped rather than
+            // rejected, so a future Docker release adding fields doesn't break decoding.
+            (_, 0) => {
+                let (_, consumed) = read_varint(&buf[pos..])?;
+                pos += consumed;
+            }
+            (_, 2) => {
+                let (_, consumed) = read_length_delimited(&buf[pos..])?;
+                pos += consumed;
+            }
+            (_, wire_type) => return Err(anyhow!("unsupported protobuf wire type {} on field {}", wire_type, field_number)),
+        }
+    }
+
+    Ok(entry)
+}
+
+fn read_varint(buf: &[u8]) -> Result<(u64, usize)> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    for (i, &byte) in buf.iter().enumerate() {
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+        shift += 7;
+        if shift >= 64 {
+            return Err(anyhow!("varint too long"));
+        }
+    }
+    Err(anyhow!("truncated varint"))
+}
+
+fn read_length_delimited(buf: &[u8]) -> Result<(&[u8], usize)> {
+    let (len, len_size) = read_varint(buf)?;
+    let len = len as usize;
+    let end = len_size + len;
+    buf.get(len_size..end).map(|bytes| (bytes, end)).ok_or_else(|| anyhow!("truncated length-delimited field"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_tag(field_number: u64, wire_type: u64) -> Vec<u8> {
+        encode_varint((field_number << 3) | wire_type)
+    }
+
+    fn encode_varint(mut value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let mut byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value != 0 {
+                byte |= 0x80;
+            }
+            bytes.push(byte);
+            if value == 0 {
+                break;
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_full_log_entry() {
+        let mut buf = Vec::new();
+        buf.extend(encode_tag(1, 2));
+        buf.extend(encode_varint(6));
+        buf.extend(b"stdout");
+
+        buf.extend(encode_tag(2, 0));
+        buf.extend(encode_varint(1_700_000_000));
+
+        buf.extend(encode_tag(3, 2));
+        buf.extend(encode_varint(11));
+        buf.extend(b"hello world");
+
+        buf.extend(encode_tag(4, 0));
+        buf.extend(encode_varint(0));
+
+        let entry = decode(&buf).unwrap();
+        assert_eq!(entry.source, "stdout");
+        assert_eq!(entry.time_nano, 1_700_000_000);
+        assert_eq!(entry.line, b"hello world");
+        assert!(!entry.partial);
+    }
+
+    #[test]
+    fn test_decode_skips_unknown_fields() {
+        let mut buf = Vec::new();
+        buf.extend(encode_tag(99, 0));
+        buf.extend(encode_varint(42));
+
+        buf.extend(encode_tag(3, 2));
+        buf.extend(encode_varint(4));
+        buf.extend(b"data");
+
+        let entry = decode(&buf).unwrap();
+        assert_eq!(entry.line, b"data");
+    }
+}
@@ -0,0 +1,54 @@
+//! JSON request/response shapes for the Docker plugin API
+//! (https://docs.docker.com/engine/extend/plugin_api/), scoped to the `LogDriver` interface.
+//! Docker's own JSON uses PascalCase field names, hence the renames throughout.
+use serde::{Deserialize, Serialize};
+
+/// Response to `POST /Plugin.Activate`, advertising which plugin interfaces this socket
+/// implements.
+#[derive(Debug, Serialize)]
+pub struct ActivateResponse {
+    #[serde(rename = "Implements")]
+    pub implements: Vec<&'static str>,
+}
+
+impl Default for ActivateResponse {
+    fn default() -> Self {
+        Self {
+            implements: vec!["LogDriver"],
+        }
+    }
+}
+
+/// Request body for `POST /LogDriver.StartLogging`.
+#[derive(Debug, Deserialize)]
+pub struct StartLoggingRequest {
+    #[serde(rename = "File")]
+    pub file: String,
+    #[serde(rename = "Info")]
+    pub info: LogDriverInfo,
+}
+
+/// The subset of Docker's `Info` struct this driver needs; unrecognized fields Docker sends
+/// (there are dozens) are ignored by serde's default behavior rather than declared here.
+#[derive(Debug, Deserialize, Default)]
+pub struct LogDriverInfo {
+    #[serde(rename = "ContainerID", default)]
+    pub container_id: String,
+    #[serde(rename = "ContainerName", default)]
+    pub container_name: String,
+}
+
+/// Request body for `POST /LogDriver.StopLogging`.
+#[derive(Debug, Deserialize)]
+pub struct StopLoggingRequest {
+    #[serde(rename = "File")]
+    pub file: String,
+}
+
+/// Shared response envelope for `StartLogging`/`StopLogging`: an absent/empty `Err` means
+/// success, by Docker plugin API convention.
+#[derive(Debug, Serialize, Default)]
+pub struct PluginResult {
+    #[serde(rename = "Err", skip_serializing_if = "Option::is_none")]
+    pub err: Option<String>,
+}
@@ -0,0 +1,62 @@
+//! Deterministic OTel trace/span id derivation, so re-exporting the same event twice (e.g. after
+//! a collector restart re-reads a checkpoint) produces the same ids rather than a duplicate
+//! trace, and so spans can be parented without any shared exporter-side state.
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Stable 16-byte trace id for every span belonging to `session_id`.
+pub fn trace_id_for_session(session_id: &str) -> [u8; 16] {
+    let mut id = [0u8; 16];
+    id[..8].copy_from_slice(&hash_u64(session_id.as_bytes(), 0).to_be_bytes());
+    id[8..].copy_from_slice(&hash_u64(session_id.as_bytes(), 1).to_be_bytes());
+    id
+}
+
+/// Stable 8-byte span id for one event.
+pub fn span_id_for_event(event_id: &str) -> [u8; 8] {
+    hash_u64(event_id.as_bytes(), 0).to_be_bytes()
+}
+
+/// Span id every non-root event in a session parents onto. Derived from the session id alone
+/// (not the `session_start` event's own id) so the parent is known before, or even without,
+/// that event ever being seen.
+pub fn session_root_span_id(session_id: &str) -> [u8; 8] {
+    hash_u64(format!("{session_id}:root").as_bytes(), 0).to_be_bytes()
+}
+
+fn hash_u64(bytes: &[u8], salt: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    salt.hash(&mut hasher);
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_id_for_session_is_deterministic_and_session_specific() {
+        assert_eq!(trace_id_for_session("sess-1"), trace_id_for_session("sess-1"));
+        assert_ne!(trace_id_for_session("sess-1"), trace_id_for_session("sess-2"));
+    }
+
+    #[test]
+    fn test_span_id_for_event_is_deterministic_and_event_specific() {
+        assert_eq!(span_id_for_event("evt-1"), span_id_for_event("evt-1"));
+        assert_ne!(span_id_for_event("evt-1"), span_id_for_event("evt-2"));
+    }
+
+    #[test]
+    fn test_session_root_span_id_does_not_collide_with_a_same_named_event_id() {
+        // The root span id is derived from "{session_id}:root", not the session id alone, so a
+        // session and an event that happen to share an id can't collide onto the same span.
+        assert_ne!(session_root_span_id("sess-1"), span_id_for_event("sess-1"));
+    }
+
+    #[test]
+    fn test_session_root_span_id_is_deterministic_and_session_specific() {
+        assert_eq!(session_root_span_id("sess-1"), session_root_span_id("sess-1"));
+        assert_ne!(session_root_span_id("sess-1"), session_root_span_id("sess-2"));
+    }
+}
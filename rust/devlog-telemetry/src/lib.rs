@@ -0,0 +1,147 @@
+//! Optional OTLP exporter: mirrors ingested `AgentEvent`s into OpenTelemetry spans and records
+//! summary token/cost counters, so operators can point any OTel collector at the collector's
+//! event stream without it replacing the SQLite `Buffer` as the system of record. Wired into
+//! `AppState` as `Option<Arc<Exporter>>`, so it's simply absent when no `telemetry.otlpEndpoint`
+//! is configured.
+use anyhow::{Context, Result};
+use devlog_core::{AgentEvent, EVENT_TYPE_SESSION_END, EVENT_TYPE_SESSION_START};
+use opentelemetry::metrics::{Counter, Meter};
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, Tracer};
+use opentelemetry::{Context as OtelContext, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::Tracer as SdkTracer;
+use serde_json::Value;
+
+mod ids;
+
+use ids::{session_root_span_id, span_id_for_event, trace_id_for_session};
+
+pub struct Config {
+    pub otlp_endpoint: String,
+    pub service_name: String,
+}
+
+pub struct Exporter {
+    tracer: SdkTracer,
+    tokens_counter: Counter<u64>,
+    cost_counter: Counter<f64>,
+}
+
+impl Exporter {
+    pub fn new(config: Config) -> Result<Self> {
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(&config.otlp_endpoint))
+            .install_batch(opentelemetry_sdk::runtime::Tokio)
+            .context("failed to install OTLP trace pipeline")?;
+
+        let meter_provider = opentelemetry_otlp::new_pipeline()
+            .metrics(opentelemetry_sdk::runtime::Tokio)
+            .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(&config.otlp_endpoint))
+            .build()
+            .context("failed to install OTLP metrics pipeline")?;
+
+        let meter: Meter = meter_provider.meter(config.service_name);
+        let tokens_counter = meter.u64_counter("devlog.tokens").init();
+        let cost_counter = meter.f64_counter("devlog.cost").init();
+
+        Ok(Self { tracer, tokens_counter, cost_counter })
+    }
+
+    /// Emit one span for `event` plus its token/cost counter contribution. Never fails: a
+    /// telemetry backend outage should never block event ingestion, so export errors are left to
+    /// the OTLP SDK's own internal logging rather than surfaced to the caller.
+    pub fn record(&self, event: &AgentEvent) {
+        self.record_span(event);
+        self.record_metrics(event);
+    }
+
+    fn record_span(&self, event: &AgentEvent) {
+        let trace_id = TraceId::from_bytes(trace_id_for_session(&event.session_id));
+        let span_id = SpanId::from_bytes(span_id_for_event(&event.id));
+
+        // `session_start`/`session_end` are the trace's root; every other event in the session
+        // parents onto a span id derived from the session id alone, so which event happens to
+        // arrive first never affects the resulting span tree.
+        let is_root = event.event_type == EVENT_TYPE_SESSION_START || event.event_type == EVENT_TYPE_SESSION_END;
+        let parent_cx = if is_root {
+            OtelContext::new()
+        } else {
+            let parent_span_context = SpanContext::new(
+                trace_id,
+                SpanId::from_bytes(session_root_span_id(&event.session_id)),
+                TraceFlags::SAMPLED,
+                true,
+                TraceState::default(),
+            );
+            OtelContext::new().with_remote_span_context(parent_span_context)
+        };
+
+        let mut builder = self
+            .tracer
+            .span_builder(event.event_type.clone())
+            .with_trace_id(trace_id)
+            .with_span_id(span_id)
+            .with_start_time(event.timestamp)
+            .with_attributes(attributes_for(event));
+
+        if let Some(duration_ms) = event.metrics.as_ref().and_then(|m| m.duration_ms) {
+            builder = builder.with_end_time(event.timestamp + chrono::Duration::milliseconds(duration_ms));
+        }
+
+        self.tracer.build_with_context(builder, &parent_cx).end();
+    }
+
+    fn record_metrics(&self, event: &AgentEvent) {
+        let Some(metrics) = &event.metrics else { return };
+        let labels = [KeyValue::new("agent_id", event.agent_id.clone())];
+
+        let tokens = metrics.prompt_tokens.unwrap_or(0) + metrics.response_tokens.unwrap_or(0);
+        if tokens > 0 {
+            self.tokens_counter.add(tokens as u64, &labels);
+        }
+        if let Some(cost) = metrics.cost {
+            self.cost_counter.add(cost, &labels);
+        }
+    }
+}
+
+/// `agent_id`/`agent_version`/`project_id` plus every `context`/`data` key, flattened to
+/// dot-joined scalar attributes (OTel attribute values can't carry arbitrary JSON).
+fn attributes_for(event: &AgentEvent) -> Vec<KeyValue> {
+    let mut attributes = vec![
+        KeyValue::new("agent_id", event.agent_id.clone()),
+        KeyValue::new("agent_version", event.agent_version.clone()),
+        KeyValue::new("project_id", event.project_id as i64),
+    ];
+
+    for (key, value) in &event.context {
+        flatten_into(&mut attributes, key, value);
+    }
+    for (key, value) in &event.data {
+        flatten_into(&mut attributes, key, value);
+    }
+
+    attributes
+}
+
+fn flatten_into(out: &mut Vec<KeyValue>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                flatten_into(out, &format!("{}.{}", prefix, k), v);
+            }
+        }
+        Value::String(s) => out.push(KeyValue::new(prefix.to_string(), s.clone())),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                out.push(KeyValue::new(prefix.to_string(), i));
+            } else if let Some(f) = n.as_f64() {
+                out.push(KeyValue::new(prefix.to_string(), f));
+            }
+        }
+        Value::Bool(b) => out.push(KeyValue::new(prefix.to_string(), *b)),
+        Value::Null => {}
+        Value::Array(_) => out.push(KeyValue::new(prefix.to_string(), value.to_string())),
+    }
+}
@@ -1,5 +1,6 @@
 use devlog_core::AgentEvent;
-use devlog_adapters::{Registry, AgentAdapter};
+use devlog_adapters::{Registry, AgentAdapter, Checkpoint};
+use devlog_buffer::SqliteStore;
 use notify::{Watcher as _, RecursiveMode, Event, EventKind};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -12,34 +13,46 @@ use std::time::Duration;
 
 pub mod discovery;
 
+/// A watched path's adapter plus the resume token from its last parse, kept in memory between
+/// file-change callbacks and mirrored to `SqliteStore::save_checkpoint` so a restart picks up from
+/// the last saved checkpoint instead of re-emitting everything.
+struct WatchedSource {
+    adapter: Arc<dyn AgentAdapter>,
+    checkpoint: Option<Checkpoint>,
+}
+
 pub struct Watcher {
     registry: Arc<Registry>,
     event_tx: mpsc::Sender<AgentEvent>,
     watcher: Box<dyn notify::Watcher + Send>,
-    watching: Arc<Mutex<HashMap<PathBuf, Arc<dyn AgentAdapter>>>>,
+    watching: Arc<Mutex<HashMap<PathBuf, WatchedSource>>>,
+    buffer: Arc<SqliteStore>,
 }
 
 pub struct Config {
     pub registry: Arc<Registry>,
     pub event_queue_size: usize,
     pub debounce_ms: u64,
+    pub buffer: Arc<SqliteStore>,
 }
 
 impl Watcher {
     pub fn new(config: Config) -> Result<(Self, mpsc::Receiver<AgentEvent>)> {
         let (tx, rx) = mpsc::channel(config.event_queue_size);
         let tx_clone = tx.clone();
-        
+
         let watching = Arc::new(Mutex::new(HashMap::new()));
         let watching_clone = watching.clone();
+        let buffer_clone = config.buffer.clone();
 
         let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
             if let Ok(event) = res {
                 let tx = tx_clone.clone();
                 let watching = watching_clone.clone();
-                
+                let buffer = buffer_clone.clone();
+
                 tokio::spawn(async move {
-                    if let Err(e) = Self::handle_event(event, tx, watching).await {
+                    if let Err(e) = Self::handle_event(event, tx, watching, buffer).await {
                         error!("Error handling file event: {}", e);
                     }
                 });
@@ -51,30 +64,38 @@ impl Watcher {
             event_tx: tx,
             watcher: Box::new(watcher),
             watching,
+            buffer: config.buffer,
         }, rx))
     }
 
     async fn handle_event(
         event: Event,
         tx: mpsc::Sender<AgentEvent>,
-        watching: Arc<Mutex<HashMap<PathBuf, Arc<dyn AgentAdapter>>>>,
+        watching: Arc<Mutex<HashMap<PathBuf, WatchedSource>>>,
+        buffer: Arc<SqliteStore>,
     ) -> Result<()> {
         match event.kind {
             EventKind::Modify(_) | EventKind::Create(_) => {
                 for path in event.paths {
                     if discovery::is_log_file(&path) {
-                        let watching_map = watching.lock().await;
-                        if let Some(adapter) = watching_map.get(&path) {
-                            let adapter = adapter.clone();
+                        let mut watching_map = watching.lock().await;
+                        if let Some(source) = watching_map.get_mut(&path) {
+                            let adapter = source.adapter.clone();
+                            let checkpoint = source.checkpoint.clone();
                             drop(watching_map);
-                            
-                            // Read and parse file
-                            // In a real implementation, we'd track the last read position
-                            // For now, we'll just parse the whole file (simplified)
-                            if let Ok(events) = adapter.parse_log_file(&path).await {
-                                for event in events {
-                                    let _ = tx.send(event).await;
-                                }
+
+                            let (events, new_checkpoint) = adapter.parse_log_file_since(&path, checkpoint).await?;
+                            for event in events {
+                                let _ = tx.send(event).await;
+                            }
+
+                            let path_key = path.to_string_lossy().to_string();
+                            let serialized = serde_json::to_string(&new_checkpoint)?;
+                            buffer.save_checkpoint(&path_key, &serialized).await?;
+
+                            let mut watching_map = watching.lock().await;
+                            if let Some(source) = watching_map.get_mut(&path) {
+                                source.checkpoint = Some(new_checkpoint);
                             }
                         }
                     }
@@ -99,7 +120,11 @@ impl Watcher {
             self.watcher.watch(&path, RecursiveMode::NonRecursive)?;
         }
 
-        watching.insert(path.clone(), adapter);
+        let path_key = path.to_string_lossy().to_string();
+        let checkpoint = self.buffer.load_checkpoint(&path_key).await?
+            .and_then(|serialized| serde_json::from_str(&serialized).ok());
+
+        watching.insert(path.clone(), WatchedSource { adapter, checkpoint });
         info!("Watching path: {}", path.display());
         Ok(())
     }